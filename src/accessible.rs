@@ -0,0 +1,383 @@
+//! Descriptions of the board in words, for screen readers.
+//!
+//! The grid printed by the board is hard to follow when read aloud,
+//!  so the accessible mode describes what each play changed instead,
+//!  and parts of the board are described when asked, see [Query].
+//!
+//! Cells are written as `row,column`, or `row,column,layer` on boards
+//!  with layers, and the meaning never depends on colours.
+//!
+
+use itertools::Itertools;
+
+use crate::analysis::mine_probabilities;
+use crate::board::topology::Topology;
+use crate::board::{Board, CellView, GameResult};
+use crate::input::plays::{Play, PlayMode, Query};
+
+/// What the player can see of a cell, in words.
+pub fn cell_state(view: CellView) -> String {
+    return match view {
+        CellView::Hidden => "hidden".to_string(),
+        CellView::Marked => "marked".to_string(),
+        CellView::Empty => "empty".to_string(),
+        CellView::Number(n) => format!("shows {}", n),
+        CellView::Mine => "the mine you dug".to_string(),
+        CellView::Blocked => "blocked".to_string(),
+    };
+}
+
+/// Auxiliary function to write the coordinates of a cell, ex: `3,4`,
+///  or `3,4,1` on boards with layers.
+fn coordinates(board: &Board, cell: (u16, u16)) -> String {
+    let (row, column, layer) = board.position(cell);
+    return match board.layers() {
+        1 => format!("{},{}", row, column),
+        _ => format!("{},{},{}", row, column, layer),
+    };
+}
+
+/// Auxiliary function to describe a cell, ex: `cell 3,4 shows 2`.
+fn describe_cell(board: &Board, cell: (u16, u16)) -> String {
+    return format!(
+        "cell {} {}",
+        coordinates(board, cell),
+        cell_state(board.cell_view(cell))
+    );
+}
+
+/// Auxiliary function to describe a line of cells, grouping the
+///  consecutive cells with the same state,
+///  ex: `columns 0 to 3 hidden, column 4 shows 1`.
+fn describe_line<I: Iterator<Item = (u16, CellView)>>(cells: I, unit: &str) -> String {
+    return cells
+        .group_by(|&(_, view)| view)
+        .into_iter()
+        .map(|(view, group)| {
+            let indexes: Vec<u16> = group.map(|(index, _)| index).collect();
+            match indexes[..] {
+                [index] => format!("{} {} {}", unit, index, cell_state(view)),
+                _ => format!(
+                    "{}s {} to {} {}",
+                    unit,
+                    indexes[0],
+                    indexes[indexes.len() - 1],
+                    cell_state(view).replacen("shows", "show", 1)
+                ),
+            }
+        })
+        .join(", ");
+}
+
+/// Auxiliary function to describe the neighbours of a cell,
+///  and how many of them are hidden or marked.
+fn describe_neighbours(board: &Board, cell: (u16, u16)) -> String {
+    let neighbours = board.neighbours(cell);
+    let count = |state: CellView| {
+        neighbours
+            .iter()
+            .filter(|&&neighbour| board.cell_view(neighbour) == state)
+            .count()
+    };
+    return format!(
+        "{}, {} neighbours, {} hidden and {} marked: {}",
+        describe_cell(board, cell),
+        neighbours.len(),
+        count(CellView::Hidden),
+        count(CellView::Marked),
+        neighbours
+            .iter()
+            .map(|&neighbour| format!(
+                "{} {}",
+                coordinates(board, neighbour),
+                cell_state(board.cell_view(neighbour))
+            ))
+            .join(", ")
+    );
+}
+
+/// Mines left, cells to dig and time, ex: `10 mines left, 52 cells to dig, 12 seconds`.
+pub fn describe_status(board: &Board) -> String {
+    return format!(
+        "{} mines left, {} cells to dig, {} seconds",
+        board.mines_left(),
+        board.cells_to_dig(),
+        board.elapsed().as_secs()
+    );
+}
+
+/// Answers a query.
+///
+/// The cursor is the cell used by queries without coordinates,
+///  and is moved to the cell of the query. Rows and columns are
+///  the ones of the layer of the cursor.
+pub fn describe(board: &Board, query: Query, cursor: &mut (u16, u16)) -> String {
+    let outside = "outside of the board".to_string();
+    let (_, _, layer) = board.position(*cursor);
+    // first row of the layer of the cursor
+    let first = layer * board.rows();
+    match query {
+        Query::Cell(cell) | Query::Neighbours(cell) => {
+            let cell = match cell {
+                None => *cursor,
+                Some((row, column, layer)) if row < board.rows() && layer < board.layers() => {
+                    (layer * board.rows() + row, column)
+                }
+                Some(_) => return format!("Cell {}", outside),
+            };
+            if !board.contains(cell) {
+                return format!("Cell {}", outside);
+            }
+            *cursor = cell;
+            return match query {
+                Query::Cell(_) => describe_cell(board, cell),
+                _ => describe_neighbours(board, cell),
+            };
+        }
+        Query::Row(row) => {
+            let row = row.unwrap_or(cursor.0 - first);
+            if row >= board.rows() {
+                return format!("Row {}", outside);
+            }
+            cursor.0 = first + row;
+            let cells =
+                (0..board.columns()).map(|column| (column, board.cell_view((first + row, column))));
+            return format!("row {}: {}", row, describe_line(cells, "column"));
+        }
+        Query::Column(column) => {
+            let column = column.unwrap_or(cursor.1);
+            if column >= board.columns() {
+                return format!("Column {}", outside);
+            }
+            cursor.1 = column;
+            let cells = (0..board.rows()).map(|row| (row, board.cell_view((first + row, column))));
+            return format!("column {}: {}", column, describe_line(cells, "row"));
+        }
+        Query::Status => return describe_status(board),
+    }
+}
+
+/// The board before the first play, ex: `board of 9 rows by 9 columns with 10 mines`.
+///
+/// Boards that aren't square start with their topology, ex: `hexagonal board of...`,
+///  boards with layers give them after the columns, ex: `...by 9 columns by 3 layers...`,
+///  and boards with a custom neighbourhood, a mask or that wrap say so at the end.
+///  Cells blocked by the mask are described as `blocked`.
+pub fn describe_board(board: &Board) -> String {
+    let topology = match board.topology() {
+        Topology::Square => String::new(),
+        topology => format!("{} ", topology),
+    };
+    let neighbourhood = match board.neighbourhood() {
+        Some(neighbourhood) => format!(
+            ", the numbers count the {} neighbourhood",
+            neighbourhood.name().unwrap_or("custom")
+        ),
+        None => String::new(),
+    };
+    let mask = match board.mask() {
+        Some(mask) => format!(
+            ", {} cells are blocked by the {} mask",
+            (board.rows() as u32 * board.columns() as u32) - mask.playable(),
+            mask.name().unwrap_or("custom")
+        ),
+        None => String::new(),
+    };
+    let wrap = match board.wraps() {
+        true => ", the edges wrap around",
+        false => "",
+    };
+    let layers = match board.layers() {
+        1 => String::new(),
+        layers => format!(" by {} layers", layers),
+    };
+    return format!(
+        "{}board of {} rows by {} columns{} with {} mines{}{}{}",
+        topology,
+        board.rows(),
+        board.columns(),
+        layers,
+        board.mines_count(),
+        neighbourhood,
+        mask,
+        wrap
+    );
+}
+
+/// Auxiliary function to find the mine that ended the game.
+fn deadly_mine(board: &Board) -> Option<(u16, u16)> {
+    return board
+        .cells()
+        .find(|&cell| board.cell_view(cell) == CellView::Mine);
+}
+
+/// Result of the game in words, with where the mine was if it was lost.
+pub fn describe_result(board: &Board) -> String {
+    return match (board.result(), deadly_mine(board)) {
+        (GameResult::Won, _) => "You won".to_string(),
+        (GameResult::Lost, Some(mine)) => {
+            format!("You lost, cell {} was a mine", coordinates(board, mine))
+        }
+        (GameResult::Lost, None) => "You lost".to_string(),
+        (GameResult::Continue, _) => describe_status(board),
+    };
+}
+
+/// What a play changed, ex: `revealed 12 cells, cell 3,4 shows 2`.
+///
+/// `revealed` is the number of cells dug by the play,
+///  which must be on the board.
+pub fn describe_play(board: &Board, play: &Play, revealed: i64) -> String {
+    let target = play
+        .unlayered(board.rows(), board.layers())
+        .unwrap_or(*play);
+    let cell = describe_cell(board, target.cell());
+    return match play.mode() {
+        PlayMode::Dig | PlayMode::Chord if board.result() == GameResult::Lost => {
+            match deadly_mine(board) {
+                Some(mine) => format!("cell {} was a mine", coordinates(board, mine)),
+                None => cell,
+            }
+        }
+        PlayMode::Dig | PlayMode::Chord if revealed == 0 => {
+            format!("nothing revealed, {}", cell)
+        }
+        PlayMode::Dig | PlayMode::Chord if revealed == 1 => format!("revealed 1 cell, {}", cell),
+        PlayMode::Dig | PlayMode::Chord => format!("revealed {} cells, {}", revealed, cell),
+        PlayMode::Mark | PlayMode::Unmark => {
+            format!("{}, {} mines left", cell, board.mines_left())
+        }
+    };
+}
+
+/// What undo or redo changed, ex: `7 cells hidden again, 10 mines left`.
+///
+/// `revealed` is the number of cells dug, negative if they were hidden.
+pub fn describe_history(board: &Board, revealed: i64) -> String {
+    let cells = match revealed {
+        0 => "no cells revealed or hidden".to_string(),
+        1 => "1 cell revealed".to_string(),
+        -1 => "1 cell hidden again".to_string(),
+        n if n > 0 => format!("{} cells revealed", n),
+        n => format!("{} cells hidden again", -n),
+    };
+    return format!("{}, {} mines left", cells, board.mines_left());
+}
+
+/// The probability of the cell having a mine and the safest cell, in words.
+pub fn describe_probabilities(board: &Board, cell: (u16, u16)) -> String {
+    let probabilities = mine_probabilities(board);
+    let description = match probabilities.get(cell) {
+        Some(probability) => format!(
+            "cell {} has {}{:.0}% chance of a mine",
+            coordinates(board, cell),
+            match probabilities.is_approximated(cell) {
+                true => "about ",
+                false => "",
+            },
+            probability * 100.0
+        ),
+        None => describe_cell(board, cell),
+    };
+    return match probabilities.safest() {
+        Some((safest, probability)) => format!(
+            "{}, the safest cell is {} with {:.0}%",
+            description,
+            coordinates(board, safest),
+            probability * 100.0
+        ),
+        None => description,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::from_grid;
+    use crate::board::mask::Mask;
+    use crate::board::neighbourhood::Neighbourhood;
+    use crate::input::arguments::Parameters;
+
+    /// Auxiliary function to apply a play, returning its description.
+    fn play(board: &mut Board, mode: PlayMode, cell: (u16, u16)) -> String {
+        let before = board.cells_to_dig() as i64;
+        match mode {
+            PlayMode::Dig => board.dig(cell),
+            PlayMode::Chord => board.chord(cell),
+            PlayMode::Mark => {
+                board.mark(cell);
+                board.result()
+            }
+            PlayMode::Unmark => {
+                board.unmark(cell);
+                board.result()
+            }
+        };
+        let revealed = before - board.cells_to_dig() as i64;
+        return describe_play(board, &Play::new(mode, cell), revealed);
+    }
+
+    #[test]
+    fn boards_are_described() {
+        let board = from_grid(&["*...", "....", "...*", "*..."]);
+        assert_eq!(
+            describe_board(&board),
+            "board of 4 rows by 4 columns with 3 mines"
+        );
+
+        let params = Parameters::layered(5, 5, 2, 4)
+            .unwrap()
+            .with_neighbourhood(Neighbourhood::preset("cross").unwrap())
+            .unwrap()
+            .with_mask("xxxxx/xxxxx/x.x.x/xxxxx/xxxxx".parse::<Mask>().unwrap())
+            .unwrap()
+            .with_wrap()
+            .unwrap();
+        assert_eq!(
+            describe_board(&Board::new(&params)),
+            "board of 5 rows by 5 columns by 2 layers with 4 mines, the numbers count \
+             the cross neighbourhood, 2 cells are blocked by the custom mask, \
+             the edges wrap around"
+        );
+    }
+
+    #[test]
+    fn plays_are_described() {
+        let mut board = from_grid(&["*...", "....", "...*", "*..."]);
+        assert_eq!(
+            play(&mut board, PlayMode::Dig, (0, 3)),
+            "revealed 6 cells, cell 0,3 empty"
+        );
+        assert_eq!(
+            play(&mut board, PlayMode::Dig, (0, 3)),
+            "nothing revealed, cell 0,3 empty"
+        );
+        assert_eq!(
+            play(&mut board, PlayMode::Dig, (2, 0)),
+            "revealed 1 cell, cell 2,0 shows 1"
+        );
+        assert_eq!(
+            play(&mut board, PlayMode::Mark, (0, 0)),
+            "cell 0,0 marked, 2 mines left"
+        );
+        assert_eq!(
+            play(&mut board, PlayMode::Dig, (3, 0)),
+            "cell 3,0 was a mine"
+        );
+    }
+
+    #[test]
+    fn probabilities_are_described() {
+        let mut board = from_grid(&["*...", "....", "...*", "*..."]);
+        board.dig((0, 3));
+        // the mine next to 0,1 is also the one of 1,1, so 2,0 is safe
+        assert_eq!(
+            describe_probabilities(&board, (3, 1)),
+            "cell 3,1 has 25% chance of a mine, the safest cell is 2,0 with 0%"
+        );
+        assert_eq!(
+            describe_probabilities(&board, (0, 3)),
+            "cell 0,3 empty, the safest cell is 2,0 with 0%"
+        );
+    }
+}
@@ -0,0 +1,710 @@
+//! Analysis of the state of the board that the player can see.
+//!
+//! Computes the probability of each undug cell having a mine,
+//!  exact below [MAX_GROUP_CELLS] (24) frontier cells.
+//! As the [solver](crate::solver), only what a [CellView] exposes is used
+//!  and the flags of the player are not trusted.
+//!
+//! The undug cells next to dug numbers (the border) are split in groups
+//!  that share no number. All the mine placements of each group that
+//!  agree with its numbers are enumerated. Each combination of solutions
+//!  of the groups is then weighted by the number of ways to place the
+//!  remaining mines on the other undug cells (the interior):
+//!  `C(interior cells, remaining mines)`.
+//!
+//! The enumeration is exponential on the size of the groups, so the
+//!  groups with more than [MAX_GROUP_CELLS] cells are approximated
+//!  instead: the cells the [solver](crate::solver) proves are mines or
+//!  safe get 1 or 0, the others get the mine density of their numbers
+//!  and the mines of the group follow a normal distribution around the
+//!  sum of them. As that changes the weights of all the cells, all but
+//!  the proved ones are then flagged as
+//!  [approximated](Probabilities::is_approximated).
+//!
+
+use std::fmt;
+use std::fmt::Formatter;
+
+use crate::board::topology::Topology;
+use crate::board::{Board, CellView, BLOCKED};
+use crate::solver::{deduce, visible_constraints, Constraint};
+
+/// Biggest group whose solutions are enumerated, see the [module](self) documentation
+pub const MAX_GROUP_CELLS: usize = 24;
+
+/// Probability of each undug cell having a mine.
+pub struct Probabilities {
+    /// What the player can see of each cell, to show
+    ///  the probabilities over the board.
+    views: Vec<Vec<CellView>>,
+    /// Probability of each cell, `None` for dug cells.
+    cells: Vec<Vec<Option<f64>>>,
+    /// If the probability of each cell is an estimate.
+    approximated: Vec<Vec<bool>>,
+    /// Topology of the board, to shift the rows as on the board.
+    topology: Topology,
+    /// Rows of each layer of the board, the rows of all
+    ///  the layers are numbered one after another.
+    rows: u16,
+}
+
+impl Probabilities {
+    /// Probability of the cell having a mine.
+    ///
+    /// `None` if the cell is already dug.
+    pub fn get(&self, (row, col): (u16, u16)) -> Option<f64> {
+        return self.cells[row as usize][col as usize];
+    }
+
+    /// If the probability of the cell is an estimate,
+    ///  see the [module](self) documentation.
+    pub fn is_approximated(&self, (row, col): (u16, u16)) -> bool {
+        return self.approximated[row as usize][col as usize];
+    }
+
+    /// All the undug cells with their probability of having a mine.
+    pub fn iter(&self) -> impl Iterator<Item = ((u16, u16), f64)> + '_ {
+        return self.cells.iter().enumerate().flat_map(|(row, cells)| {
+            cells
+                .iter()
+                .enumerate()
+                .filter_map(move |(col, p)| p.map(|p| ((row as u16, col as u16), p)))
+        });
+    }
+
+    /// The undug cell less likely to have a mine.
+    pub fn safest(&self) -> Option<((u16, u16), f64)> {
+        return self.iter().fold(None, |best, (cell, p)| match best {
+            Some((_, best_p)) if best_p <= p => best,
+            _ => Some((cell, p)),
+        });
+    }
+}
+
+impl fmt::Display for Probabilities {
+    /// Shows the board with the percentage of each undug cell
+    ///  having a mine, with a `~` before the estimated ones.
+    ///  Dug cells are shown as on the board.
+    ///
+    /// Boards with layers show them one after another,
+    ///  each with its number above.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let columns = self.views.first().map(|row| row.len()).unwrap_or(0);
+        let rows = self.rows as usize;
+        // room for `100%`, or `~99%`
+        let mut width = rows.max(columns).to_string().len().max(4);
+        if self.topology == Topology::Hexagonal && width.is_multiple_of(2) {
+            width += 1;
+        }
+        let shift = width.div_ceil(2);
+
+        for (row, ((views, cells), approximated)) in self
+            .views
+            .iter()
+            .zip(self.cells.iter())
+            .zip(self.approximated.iter())
+            .enumerate()
+        {
+            let i = row % rows;
+            if i == 0 {
+                if self.views.len() > rows {
+                    writeln!(f, "layer {}", row / rows)?;
+                }
+                write!(f, "{:1$}", "", width + 1)?;
+                for i in 0..columns {
+                    write!(f, "{:1$} ", i, width)?;
+                }
+                writeln!(f)?;
+            }
+
+            let shifted = self.topology.is_shifted(i as u16);
+            write!(f, "{:1$} ", i, width)?;
+            if shifted {
+                write!(f, "{:1$}", "", shift)?;
+            }
+            for ((view, p), approximated) in views.iter().zip(cells.iter()).zip(approximated) {
+                match (p, view) {
+                    (Some(p), _) if *approximated => {
+                        // an estimate is never certain, so never shown as `100%`
+                        let percentage = (p * 100.0).round().min(99.0);
+                        write!(f, "{:>1$}", format!("~{}%", percentage), width)?
+                    }
+                    (Some(p), _) => write!(f, "{:>1$}", format!("{:.0}%", p * 100.0), width)?,
+                    (None, CellView::Number(n)) => write!(f, "{:1$}", n, width)?,
+                    (None, CellView::Mine) => write!(f, "{:>1$}", "X", width)?,
+                    (None, CellView::Blocked) => write!(f, "{:>1$}", BLOCKED, width)?,
+                    (None, _) => write!(f, "{:>1$}", ".", width)?,
+                }
+                write!(f, " ")?;
+            }
+            if self.topology == Topology::Hexagonal && !shifted {
+                write!(f, "{:1$}", "", shift)?;
+            }
+            writeln!(f, " {}", i)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A group of border cells that share numbers with each other
+///  but not with the cells of other groups.
+struct Group {
+    /// Indexes of the undug cells
+    cells: Vec<usize>,
+    /// Constraints over the cells of the group, with the
+    ///  cells as indexes of [cells](Group::cells)
+    constraints: Vec<Constraint>,
+}
+
+/// Solutions of a [Group], grouped by number of mines used.
+///
+/// The counts are scaled by the same factor, so they don't overflow.
+/// Only the ratios between them matter.
+struct GroupSolutions {
+    /// `count[k]`: solutions with `k` mines
+    count: Vec<f64>,
+    /// `cell_count[k][i]`: solutions with `k` mines and a mine on cell `i`
+    cell_count: Vec<Vec<f64>>,
+    /// Probability of each cell when the group is too big to enumerate
+    ///  its solutions, then [cell_count](GroupSolutions::cell_count) is empty.
+    approximation: Option<Vec<f64>>,
+}
+
+/// Auxiliary function to split the constraints into independent groups.
+fn groups(unknown: usize, constraints: Vec<Constraint>) -> Vec<Group> {
+    // union-find over the undug cells
+    let mut parent = (0..unknown).collect::<Vec<usize>>();
+    fn find(parent: &mut [usize], cell: usize) -> usize {
+        let mut root = cell;
+        while parent[root] != root {
+            root = parent[root];
+        }
+        parent[cell] = root;
+        return root;
+    }
+
+    for constraint in constraints.iter() {
+        let first = find(&mut parent, constraint.cells[0]);
+        for cell in constraint.cells.iter().skip(1) {
+            let root = find(&mut parent, *cell);
+            parent[root] = first;
+        }
+    }
+
+    let mut groups: Vec<Group> = Vec::new();
+    let mut group_of_root = vec![None; unknown];
+    // index of each cell on its group, usize::MAX if not added yet
+    let mut local_index = vec![usize::MAX; unknown];
+    for constraint in constraints {
+        let root = find(&mut parent, constraint.cells[0]);
+        let group_index = *group_of_root[root].get_or_insert_with(|| {
+            groups.push(Group {
+                cells: Vec::new(),
+                constraints: Vec::new(),
+            });
+            groups.len() - 1
+        });
+        let group = &mut groups[group_index];
+
+        let mut cells = Vec::with_capacity(constraint.cells.len());
+        for cell in constraint.cells {
+            if local_index[cell] == usize::MAX {
+                local_index[cell] = group.cells.len();
+                group.cells.push(cell);
+            }
+            cells.push(local_index[cell]);
+        }
+        group.constraints.push(Constraint {
+            cells,
+            mines: constraint.mines,
+        });
+    }
+
+    return groups;
+}
+
+/// Auxiliary function to enumerate all the solutions of a group.
+///
+/// Cells are assigned in the order they were added to the group,
+///  which follows the constraints, so they are checked early.
+fn enumerate_group(group: &Group, max_mines: usize) -> GroupSolutions {
+    let size = group.cells.len();
+
+    // for each cell, the constraints it belongs to
+    let mut cell_constraints = vec![Vec::new(); size];
+    for (i, constraint) in group.constraints.iter().enumerate() {
+        for cell in constraint.cells.iter() {
+            cell_constraints[*cell].push(i);
+        }
+    }
+
+    let mut solutions = GroupSolutions {
+        count: vec![0.0; size + 1],
+        cell_count: vec![vec![0.0; size]; size + 1],
+        approximation: None,
+    };
+
+    // current state of each constraint: mines placed and cells not assigned yet
+    let mut placed = vec![0u32; group.constraints.len()];
+    let mut free = group
+        .constraints
+        .iter()
+        .map(|constraint| constraint.cells.len() as u32)
+        .collect::<Vec<u32>>();
+    let mut assignment = vec![false; size];
+
+    // iterative depth first search: (cell, value to try next)
+    let mut stack = vec![(0usize, 0u8)];
+    while let Some((cell, value)) = stack.pop() {
+        if cell == size {
+            let mines = assignment.iter().filter(|mine| **mine).count();
+            if mines <= max_mines {
+                solutions.count[mines] += 1.0;
+                for (i, mine) in assignment.iter().enumerate() {
+                    if *mine {
+                        solutions.cell_count[mines][i] += 1.0;
+                    }
+                }
+            }
+            continue;
+        }
+
+        // undo the previous value of this cell before trying the next
+        if value > 0 {
+            let was_mine = assignment[cell];
+            for constraint in cell_constraints[cell].iter() {
+                free[*constraint] += 1;
+                if was_mine {
+                    placed[*constraint] -= 1;
+                }
+            }
+            assignment[cell] = false;
+        }
+        if value == 2 {
+            continue;
+        }
+
+        let mine = value == 1;
+        let mut valid = true;
+        for constraint in cell_constraints[cell].iter() {
+            free[*constraint] -= 1;
+            if mine {
+                placed[*constraint] += 1;
+            }
+            let target = group.constraints[*constraint].mines;
+            if placed[*constraint] > target || placed[*constraint] + free[*constraint] < target {
+                valid = false;
+            }
+        }
+        assignment[cell] = mine;
+
+        stack.push((cell, value + 1));
+        if valid {
+            stack.push((cell + 1, 0));
+        }
+    }
+
+    // scale so the biggest count is 1
+    let max = solutions.count.iter().cloned().fold(0.0, f64::max);
+    if max > 0.0 {
+        solutions.count.iter_mut().for_each(|c| *c /= max);
+        solutions
+            .cell_count
+            .iter_mut()
+            .flatten()
+            .for_each(|c| *c /= max);
+    }
+
+    return solutions;
+}
+
+/// Auxiliary function to approximate the solutions of a group too big to enumerate.
+///
+/// `known` are the deductions of the [solver](crate::solver) for all the undug
+///  cells. The cells it proves are mines or safe get 1 or 0, otherwise a cell
+///  is safe or a mine if one of its numbers says so, or its probability is
+///  the average of the mine densities of its numbers without the known cells.
+///  The distribution of the mines of the group is a normal one with
+///  the mean and variance of independent cells with those probabilities.
+fn approximate_group(group: &Group, known: &[Option<bool>], max_mines: usize) -> GroupSolutions {
+    let size = group.cells.len();
+    let known = group
+        .cells
+        .iter()
+        .map(|cell| known[*cell])
+        .collect::<Vec<Option<bool>>>();
+
+    let mut densities = vec![Vec::new(); size];
+    for constraint in group.constraints.iter() {
+        let (left, known_cells): (Vec<usize>, Vec<usize>) = constraint
+            .cells
+            .iter()
+            .partition(|cell| known[**cell].is_none());
+        if left.is_empty() {
+            continue;
+        }
+        let known_mines = known_cells
+            .iter()
+            .filter(|cell| known[**cell] == Some(true))
+            .count() as u32;
+        let density = (constraint.mines - known_mines) as f64 / left.len() as f64;
+        for cell in left {
+            densities[cell].push(density);
+        }
+    }
+    let probabilities = densities
+        .iter()
+        .zip(known.iter())
+        .map(|(densities, known)| {
+            if let Some(mine) = known {
+                match mine {
+                    true => 1.0,
+                    false => 0.0,
+                }
+            } else if densities.contains(&0.0) {
+                0.0
+            } else if densities.contains(&1.0) {
+                1.0
+            } else {
+                densities.iter().sum::<f64>() / densities.len() as f64
+            }
+        })
+        .collect::<Vec<f64>>();
+
+    // so some of the counts are left after dropping the ones with too many mines
+    let mean = probabilities.iter().sum::<f64>().min(max_mines as f64);
+    let variance = probabilities.iter().map(|p| p * (1.0 - p)).sum::<f64>();
+    let count = (0..=size)
+        .map(|mines| {
+            if mines > max_mines {
+                0.0
+            } else if variance == 0.0 {
+                match mines == mean.round() as usize {
+                    true => 1.0,
+                    false => 0.0,
+                }
+            } else {
+                (-(mines as f64 - mean).powi(2) / (2.0 * variance)).exp()
+            }
+        })
+        .collect();
+
+    return GroupSolutions {
+        count,
+        cell_count: Vec::new(),
+        approximation: Some(probabilities),
+    };
+}
+
+/// Auxiliary function to convolve two distributions of mines.
+fn convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut result = vec![0.0; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        if *x == 0.0 {
+            continue;
+        }
+        for (j, y) in b.iter().enumerate() {
+            result[i + j] += x * y;
+        }
+    }
+    return result;
+}
+
+/// Auxiliary function to compute the weight of placing `mines - s` mines
+///  on the `interior` cells, for each `s` from `0` to `border`.
+///
+/// The weights are `C(interior, mines - s)` scaled by the same factor.
+fn interior_weights(interior: u64, mines: u64, border: usize) -> Vec<f64> {
+    // ln C(interior, r), for r from mines down to mines - border
+    let ln_choose = |r: u64| -> f64 {
+        let r = r.min(interior - r);
+        return (0..r)
+            .map(|i| ((interior - i) as f64).ln() - ((i + 1) as f64).ln())
+            .sum();
+    };
+
+    let logs = (0..=border as u64)
+        .map(|s| {
+            if s > mines || mines - s > interior {
+                return None;
+            }
+            return Some(ln_choose(mines - s));
+        })
+        .collect::<Vec<Option<f64>>>();
+
+    let max = logs
+        .iter()
+        .flatten()
+        .cloned()
+        .fold(f64::NEG_INFINITY, f64::max);
+    return logs
+        .iter()
+        .map(|log| log.map(|log| (log - max).exp()).unwrap_or(0.0))
+        .collect();
+}
+
+/// Computes the probability of each undug cell having a mine,
+///  exact below [MAX_GROUP_CELLS] (24) frontier cells.
+///
+/// See the [module](self) documentation for how it's done.
+pub fn mine_probabilities(board: &Board) -> Probabilities {
+    let (unknown, constraints) = visible_constraints(board);
+    let mines = board.mines_count() as u64;
+
+    let groups = groups(unknown.len(), constraints);
+    let border = groups.iter().map(|group| group.cells.len()).sum::<usize>();
+    let interior = (unknown.len() - border) as u64;
+
+    // what the solver proves, only needed to approximate the big groups
+    let known = match groups
+        .iter()
+        .any(|group| group.cells.len() > MAX_GROUP_CELLS)
+    {
+        true => deduce(board),
+        false => Vec::new(),
+    };
+    let solutions = groups
+        .iter()
+        .map(|group| match group.cells.len() {
+            size if size > MAX_GROUP_CELLS => approximate_group(group, &known, mines as usize),
+            _ => enumerate_group(group, mines as usize),
+        })
+        .collect::<Vec<GroupSolutions>>();
+
+    // distributions of mines of all groups before and after each group
+    let mut prefix = vec![vec![1.0]];
+    for group in solutions.iter() {
+        let last = prefix.last().unwrap();
+        prefix.push(convolve(last, &group.count));
+    }
+    let mut suffix = vec![vec![1.0]];
+    for group in solutions.iter().rev() {
+        let last = suffix.last().unwrap();
+        suffix.push(convolve(last, &group.count));
+    }
+    suffix.reverse();
+
+    let weights = interior_weights(interior, mines, border);
+    let total_distribution = &prefix[solutions.len()];
+    let total = total_distribution
+        .iter()
+        .zip(weights.iter())
+        .map(|(count, weight)| count * weight)
+        .sum::<f64>();
+
+    let mut probabilities = vec![None; unknown.len()];
+
+    for (g, (group, group_solutions)) in groups.iter().zip(solutions.iter()).enumerate() {
+        if let Some(approximation) = &group_solutions.approximation {
+            for (cell, p) in group.cells.iter().zip(approximation.iter()) {
+                probabilities[*cell] = Some(*p);
+            }
+            continue;
+        }
+        let others = convolve(&prefix[g], &suffix[g + 1]);
+        for (i, cell) in group.cells.iter().enumerate() {
+            let mut weight = 0.0;
+            for (k, cell_counts) in group_solutions.cell_count.iter().enumerate() {
+                if cell_counts[i] == 0.0 {
+                    continue;
+                }
+                for (s, count) in others.iter().enumerate() {
+                    weight += cell_counts[i] * count * weights[k + s];
+                }
+            }
+            probabilities[*cell] = Some(weight / total);
+        }
+    }
+
+    // all the interior cells have the same probability:
+    //  the expected remaining mines over the interior cells
+    if interior > 0 {
+        let expected = total_distribution
+            .iter()
+            .zip(weights.iter())
+            .enumerate()
+            .filter(|(s, _)| *s as u64 <= mines)
+            .map(|(s, (count, weight))| count * weight * (mines - s as u64) as f64)
+            .sum::<f64>();
+        let p = expected / total / interior as f64;
+        for probability in probabilities.iter_mut() {
+            if probability.is_none() {
+                *probability = Some(p);
+            }
+        }
+    }
+
+    // the approximated groups change the weights of all the other
+    //  cells, only the ones the solver proves are exact
+    let approximated = match known.is_empty() {
+        true => vec![false; unknown.len()],
+        false => known.iter().map(|known| known.is_none()).collect(),
+    };
+
+    // the rows of all the layers
+    let rows = board.rows() * board.layers();
+    let mut views = Vec::with_capacity(rows as usize);
+    let mut cells = vec![vec![None; board.columns() as usize]; rows as usize];
+    let mut approximations = vec![vec![false; board.columns() as usize]; rows as usize];
+    for row in 0..rows {
+        views.push(
+            (0..board.columns())
+                .map(|col| board.cell_view((row, col)))
+                .collect(),
+        );
+    }
+    for ((cell, p), approximated) in unknown.iter().zip(probabilities).zip(approximated) {
+        cells[cell.0 as usize][cell.1 as usize] = p;
+        approximations[cell.0 as usize][cell.1 as usize] = approximated;
+    }
+
+    return Probabilities {
+        views,
+        cells,
+        approximated: approximations,
+        topology: board.topology(),
+        rows: board.rows(),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::from_grid;
+
+    /// Auxiliary function to compute the probabilities by trying
+    ///  every placement of the mines on the undug cells.
+    fn brute_force(board: &Board) -> Vec<((u16, u16), f64)> {
+        let cells = (0..board.rows())
+            .flat_map(|row| (0..board.columns()).map(move |column| (row, column)))
+            .collect::<Vec<(u16, u16)>>();
+        let undug = cells
+            .iter()
+            .copied()
+            .filter(|cell| matches!(board.cell_view(*cell), CellView::Hidden))
+            .collect::<Vec<(u16, u16)>>();
+        let numbers = cells
+            .iter()
+            .copied()
+            .filter_map(|cell| match board.cell_view(cell) {
+                CellView::Number(n) => Some((board.neighbours(cell), n as usize)),
+                _ => None,
+            })
+            .collect::<Vec<(Vec<(u16, u16)>, usize)>>();
+
+        let mut total = 0;
+        let mut mines_on = vec![0; undug.len()];
+        let mut chosen = Vec::new();
+        fn place(
+            next: usize,
+            left: usize,
+            chosen: &mut Vec<usize>,
+            visit: &mut dyn FnMut(&[usize]),
+            undug: usize,
+        ) {
+            if left == 0 {
+                visit(chosen);
+                return;
+            }
+            for cell in next..undug {
+                chosen.push(cell);
+                place(cell + 1, left - 1, chosen, visit, undug);
+                chosen.pop();
+            }
+        }
+        let mut visit = |chosen: &[usize]| {
+            let agrees = numbers.iter().all(|(neighbours, n)| {
+                let mines = chosen
+                    .iter()
+                    .filter(|cell| neighbours.contains(&undug[**cell]))
+                    .count();
+                mines == *n
+            });
+            if agrees {
+                total += 1;
+                for cell in chosen {
+                    mines_on[*cell] += 1;
+                }
+            }
+        };
+        let mines = board.mines_count() as usize;
+        place(0, mines, &mut chosen, &mut visit, undug.len());
+
+        return undug
+            .iter()
+            .zip(mines_on)
+            .map(|(cell, mines)| (*cell, mines as f64 / total as f64))
+            .collect();
+    }
+
+    #[test]
+    fn two_cells_sharing_a_number_are_a_fifty_fifty() {
+        let board = from_grid(&["oo", "*."]);
+        let probabilities = mine_probabilities(&board);
+        assert_eq!(probabilities.get((1, 0)), Some(0.5));
+        assert_eq!(probabilities.get((1, 1)), Some(0.5));
+        assert_eq!(probabilities.get((0, 0)), None);
+    }
+
+    #[test]
+    fn solutions_are_weighted_by_the_interior_cells() {
+        // a mine on 0,1 or 1,1 agrees with both numbers, the other solutions
+        //  need two mines and leave fewer mines for the interior
+        let board = from_grid(&["o*o..", ".....", "..*..", ".....", "*...*"]);
+        let probabilities = mine_probabilities(&board);
+        for (cell, expected) in brute_force(&board) {
+            let p = probabilities.get(cell).unwrap();
+            assert!(
+                (p - expected).abs() < 1e-9,
+                "{:?}: {} != {}",
+                cell,
+                p,
+                expected
+            );
+        }
+        // the interior cells are all the same, and not the plain density
+        let interior = probabilities.get((4, 2)).unwrap();
+        assert_eq!(probabilities.get((3, 3)), Some(interior));
+        assert!((interior - 4.0 / 23.0).abs() > 1e-3);
+    }
+
+    #[test]
+    fn solved_groups_are_certain() {
+        let board = from_grid(&["oo..", "o*..", "....", ".*.*"]);
+        let probabilities = mine_probabilities(&board);
+        assert_eq!(probabilities.get((1, 1)), Some(1.0));
+        for cell in [(0, 2), (1, 2), (2, 0), (2, 1)] {
+            assert_eq!(probabilities.get(cell), Some(0.0));
+        }
+    }
+
+    #[test]
+    fn big_groups_are_approximated() {
+        // a number on each column counts a mine every 3 columns,
+        //  and a mine away from the numbers
+        let columns = 3 * MAX_GROUP_CELLS;
+        let mines = (0..columns)
+            .map(|column| match column % 3 {
+                1 => '*',
+                _ => '.',
+            })
+            .collect::<String>();
+        let dug = "o".repeat(columns);
+        let hidden = format!("*{}", ".".repeat(columns - 1));
+        let board = from_grid(&[&dug, &mines, &hidden]);
+
+        let probabilities = mine_probabilities(&board);
+        for (_, p) in probabilities.iter() {
+            assert!((0.0..=1.0).contains(&p));
+        }
+        // the solver proves the mine, the density of its numbers is 1/3
+        assert_eq!(probabilities.get((1, 4)), Some(1.0));
+        assert!(!probabilities.is_approximated((1, 4)));
+        assert_eq!(probabilities.get((1, 3)), Some(0.0));
+        // the mine away from the numbers can't be proved, and its
+        //  probability depends on the estimate of the group
+        assert!(probabilities.is_approximated((2, 4)));
+        assert!(probabilities.to_string().contains('~'));
+    }
+}
@@ -1,18 +1,47 @@
 //! All the game logic is in this module
+//!
+//! Cells are addressed by `(row, column)`. On boards with several
+//!  layers the rows of all the layers are numbered one after another,
+//!  so the rows of the layer `l` start at `l * rows`,
+//!  see [position](Board::position).
+//!
+//! Boards with a [Mask] have blocked cells, which can't be played
+//!  and are left out of everything else: they never have mines,
+//!  aren't neighbours of any cell and aren't on [cells](Board::cells).
+//!
+
+pub mod benchmark;
+mod history;
+pub mod mask;
+pub mod neighbourhood;
+pub mod replay;
+pub mod save;
+pub mod summary;
+pub mod topology;
+pub mod verify;
 
-use ansi_term;
 use itertools::{enumerate, Itertools};
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::seq::index::sample;
+use rand::{Rng, SeedableRng};
 use std::collections::HashSet;
-use std::iter::FromIterator;
+use std::time::{Duration, Instant};
 
+use crate::board::history::{CellState, Change, Counters, History, Step};
+use crate::board::mask::Mask;
+use crate::board::neighbourhood::Neighbourhood;
+use crate::board::summary::{Clicks, Summary};
+use crate::board::topology::Topology;
 use crate::input::arguments::Parameters;
+use crate::input::plays::PlayMode;
+use crate::solver::solve;
+use crate::theme::{self, Role};
 use core::fmt;
-use std::borrow::{Borrow, BorrowMut};
 use std::fmt::Formatter;
 
 /// Returned by this module so the main
 ///  function know if the game should continue.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub enum GameResult {
     Lost,
     Won,
@@ -28,37 +57,45 @@ enum CellValue {
 }
 
 impl fmt::Display for CellValue {
-    /// Color based on the ones present in http://minesweeperonline.com/
+    /// Numbers with the colours of the [current theme](theme::current)
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             CellValue::Empty => write!(f, " "),
             CellValue::Mine => write!(f, "X"),
-            CellValue::Number(n) => match n {
-                1 => write!(f, "{}", ansi_term::Color::RGB(0, 0, 255).paint("1")),
-                2 => write!(f, "{}", ansi_term::Color::RGB(0, 123, 0).paint("2")),
-                3 => write!(f, "{}", ansi_term::Color::RGB(255, 0, 0).paint("3")),
-                4 => write!(f, "{}", ansi_term::Color::RGB(0, 0, 123).paint("4")),
-                5 => write!(f, "{}", ansi_term::Color::RGB(123, 0, 0).paint("5")),
-                6 => write!(f, "{}", ansi_term::Color::RGB(0, 123, 123).paint("6")),
-                _ => write!(f, "{}", n),
-            },
+            CellValue::Number(n) => write!(f, "{}", theme::current().paint(Role::Number(*n), n)),
         }
     }
 }
 
 impl CellValue {
     /// Is self type Number.
-    ///
-    /// TODO can I do `var == CellValue::Number(_)` ?
     fn is_number(&self) -> bool {
-        match self {
-            CellValue::Number(_) => true,
-            _ => false,
-        }
+        return matches!(self, CellValue::Number(_));
     }
 }
 
+/// What the player can see of a cell.
+///
+/// Used by the users of the library to query the board
+///  without having access to the hidden values.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum CellView {
+    /// Not dug nor marked.
+    Hidden,
+    /// Marked by the player as a mine.
+    Marked,
+    /// Dug cell without adjacent mines.
+    Empty,
+    /// Dug cell with the number of adjacent mines.
+    Number(u8),
+    /// The mine dug by the player, which ended the game.
+    Mine,
+    /// Blocked by the [mask](Board::mask), can't be played.
+    Blocked,
+}
+
 /// A cell on the playing board.
+#[derive(Clone)]
 struct Cell {
     /// If the user already dug this cell.
     dug: bool,
@@ -67,13 +104,23 @@ struct Cell {
     value: CellValue,
 }
 
+impl Cell {
+    /// Characters taken by the cell when drawn, without the colours.
+    fn width(&self) -> usize {
+        return match self.value {
+            CellValue::Number(n) if self.dug && !self.marked => n.to_string().len(),
+            _ => 1,
+        };
+    }
+}
+
 impl fmt::Display for Cell {
-    /// if the cell is mark print a red `X`,
+    /// if the cell is mark print a `X` with the flag colour,
     ///  else if the user already dug the cell use
     ///  its fmt method else print `?`
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         if self.marked {
-            write!(f, "{}", ansi_term::Color::Red.paint("X"))
+            write!(f, "{}", theme::current().paint(Role::Flag, "X"))
         } else {
             if self.dug {
                 self.value.fmt(f)
@@ -85,20 +132,71 @@ impl fmt::Display for Cell {
 }
 
 /// Contains all the game data.
+#[derive(Clone)]
 pub struct Board {
-    /// Number of rows of the board
-    rows: u8,
+    /// Number of rows of each layer of the board
+    rows: u16,
     /// Number of columns of the board
-    columns: u8,
+    columns: u16,
+    /// Number of layers of the board, 1 on flat boards
+    layers: u16,
     /// Number of min on the board
-    mines_count: u8,
+    mines_count: u32,
+    /// Shape of the cells, gives the neighbours of each cell
+    topology: Topology,
+    /// Cells counted by the numbers, `None` for the neighbours of the topology
+    neighbourhood: Option<Neighbourhood>,
+    /// Cells that can't be played, the same on every layer
+    mask: Option<Mask>,
+    /// If the opposite edges of the board are next to each other
+    wrap: bool,
     /// Remaining cell to dig.
     /// Used to know if the used won.
-    cells_to_dig: u16,
+    cells_to_dig: u32,
+    /// Number of cells of the empty area created around the first play.
+    opening: u32,
     /// After the user loses this will contain
     ///  the coordinates for the mine that he
     ///  dug. Before that contains `None`.
-    deadly_mine: Option<(u8, u8)>,
+    deadly_mine: Option<(u16, u16)>,
+    /// Seed used to place the mines.
+    ///
+    /// Building the board again with the same seed and first
+    ///  play results on the same board.
+    /// Is `None` if the board was built with an external
+    ///  random generator, see [build_with_rng](Board::build_with_rng).
+    seed: Option<u64>,
+    /// If set, the board is generated again, up to this number
+    ///  of attempts, until it can be solved without guessing.
+    no_guess: Option<u32>,
+    /// Number of boards generated until one was accepted.
+    attempts: u32,
+    /// If the board was verified to be solvable without guessing.
+    guess_free: bool,
+    /// Number of cells marked as a mine.
+    marked_count: u32,
+    /// Maximum number of cells that can be marked at the same time.
+    ///
+    /// `None` if there is no limit.
+    flag_limit: Option<u32>,
+    /// Plays that can be undone and redone.
+    history: History,
+    /// While a play is being applied, the state of the cells
+    ///  before they were changed. `None` outside of plays.
+    changes: Option<Vec<((u16, u16), CellState)>>,
+    /// If undo can be used after the game ends.
+    practice: bool,
+    /// If undo was used after the game ended,
+    ///  see [is_practice](Board::is_practice).
+    undid_game_over: bool,
+    /// When the current playing session started.
+    ///
+    /// `None` before the board is built.
+    started: Option<Instant>,
+    /// Time played before the current session, ex: before the game was saved.
+    elapsed_before: Duration,
+    /// Plays done while the game was running.
+    clicks: Clicks,
 
     /// Board of cells
     board: Vec<Vec<Cell>>,
@@ -109,9 +207,11 @@ impl Board {
     ///
     /// Fills the board with cell with [Empty](CellValue::Empty) value.
     pub fn new(params: &Parameters) -> Board {
-        let mut board = Vec::<Vec<Cell>>::with_capacity(params.rows() as usize);
+        // the rows of all the layers, one layer after another
+        let rows = params.rows() * params.layers();
+        let mut board = Vec::<Vec<Cell>>::with_capacity(rows as usize);
 
-        for _ in 0..params.rows() {
+        for _ in 0..rows {
             let mut row1 = Vec::<Cell>::with_capacity(params.columns() as usize);
             for _ in 0..params.columns() {
                 row1.push(Cell {
@@ -126,91 +226,195 @@ impl Board {
         Board {
             rows: params.rows(),
             columns: params.columns(),
+            layers: params.layers(),
             mines_count: params.mines(),
-            cells_to_dig: params.rows() as u16 * params.columns() as u16,
+            topology: params.topology(),
+            neighbourhood: params.neighbourhood().cloned(),
+            mask: params.mask().cloned(),
+            wrap: params.wrap(),
+            cells_to_dig: params.cells(),
+            opening: params.opening(),
             board,
             deadly_mine: None,
+            seed: Some(params.seed().unwrap_or_else(rand::random)),
+            no_guess: params.no_guess(),
+            attempts: 0,
+            guess_free: false,
+            marked_count: 0,
+            flag_limit: params.flag_limit(),
+            history: History::default(),
+            changes: None,
+            practice: params.practice(),
+            undid_game_over: false,
+            started: None,
+            elapsed_before: Duration::ZERO,
+            clicks: Clicks::default(),
         }
     }
 
     /// Places mines and numbers on the board.
     ///
-    /// Called only after the first play.
+    /// Called only after the first play, which can't be on a
+    ///  [blocked](Board::is_blocked) cell.
+    ///
+    /// The mines are placed using a random generator
+    ///  created from the board [seed](Board::seed).
+    pub fn build(&mut self, start_cell: (u16, u16)) {
+        // a seed is always set before the board is built
+        let mut random = StdRng::seed_from_u64(self.seed.unwrap());
+        self.place_all(start_cell, &mut random);
+        self.update_timer();
+    }
+
+    /// Same as [build](Board::build) but using the given random generator.
+    ///
+    /// Allows to inject a generator, ex: on tests. Since the
+    ///  board can't be reproduced from its seed anymore,
+    ///  the [seed](Board::seed) is cleared.
+    pub fn build_with_rng<R: Rng + ?Sized>(&mut self, start_cell: (u16, u16), random: &mut R) {
+        self.seed = None;
+        self.place_all(start_cell, random);
+        self.update_timer();
+    }
+
+    /// Auxiliary method of [build](Board::build) that does the actual work.
+    ///
+    /// On no-guess mode, boards are generated with the same random
+    ///  generator until the [solver](crate::solver) can clear the board
+    ///  from the first play or the attempts run out. On the later case
+    ///  the last board is kept.
+    fn place_all<R: Rng + ?Sized>(&mut self, start_cell: (u16, u16), random: &mut R) {
+        self.resume();
+        self.clicks.count(PlayMode::Dig, true);
+        let budget = match self.no_guess {
+            Some(budget) => budget,
+            None => {
+                self.attempts = 1;
+                self.place_layout(start_cell, random);
+                return;
+            }
+        };
+
+        self.attempts = 0;
+        while self.attempts < budget {
+            if self.attempts > 0 {
+                self.clear();
+            }
+            self.attempts += 1;
+
+            self.place_layout(start_cell, random);
+            if self.can_be_solved() {
+                self.guess_free = true;
+                return;
+            }
+        }
+    }
+
+    /// Auxiliary method to go back to the state before the board was built.
+    fn clear(&mut self) {
+        for row in self.board.iter_mut() {
+            for cell in row.iter_mut() {
+                cell.dug = false;
+                cell.marked = false;
+                cell.value = CellValue::Empty;
+            }
+        }
+        self.cells_to_dig = self.size();
+        self.marked_count = 0;
+        self.history.clear();
+    }
+
+    /// Auxiliary method to check if the board can be cleared
+    ///  only by digging cells that the [solver](crate::solver)
+    ///  proves to be safe.
+    fn can_be_solved(&self) -> bool {
+        let mut board = self.clone();
+        while board.cells_to_dig > 0 {
+            let deductions = solve(&board);
+            if deductions.safe().is_empty() {
+                return false;
+            }
+            for cell in deductions.safe() {
+                board.dig(*cell);
+            }
+        }
+        return true;
+    }
+
+    /// Auxiliary method of [place_all](Board::place_all) that generates one board.
     ///
     /// Before placing mines and numbers creates
     ///  an empty area around the cell that the
-    ///  user choose on the first play.
+    ///  user choose on the first play, with the
+    ///  size given by [Parameters::opening].
     ///
-    /// TODO number of cells of the empty area is hardcoded
-    pub fn build(&mut self, start_cell: (u8, u8)) {
+    /// With a custom [neighbourhood](Parameters::neighbourhood)
+    ///  the area starts with the whole neighbourhood of the cell,
+    ///  so the first play is always an empty cell.
+    fn place_layout<R: Rng + ?Sized>(&mut self, start_cell: (u16, u16), random: &mut R) {
         self.board[start_cell.0 as usize][start_cell.1 as usize].value = CellValue::Empty;
 
-        let EMPTY_CELLS = 40; // TODO calculate this according to difficulty
-
-        let mut initial_empty_cells = Vec::with_capacity(EMPTY_CELLS);
+        let opening = self.opening as usize;
+        let mut initial_empty_cells = Vec::with_capacity(opening);
+        let mut seen = HashSet::with_capacity(opening);
 
         initial_empty_cells.push(start_cell);
+        seen.insert(start_cell);
+        if self.neighbourhood.is_some() {
+            initial_empty_cells.extend(self.generate_ring(start_cell));
+            seen.extend(self.generate_ring(start_cell));
+        }
+        // the blocked cells can leave less room around the first play
+        let opening = match self.mask {
+            Some(_) => opening.min(self.reachable(&initial_empty_cells, opening)),
+            None => opening,
+        };
 
-        let expand_possibilities = [[1, 0], [0, 1], [-1, 0], [0, -1]];
-
-        let mut random = rand::thread_rng();
-        while initial_empty_cells.len() < EMPTY_CELLS {
+        while initial_empty_cells.len() < opening {
             let cell_index = random.gen_range(0..initial_empty_cells.len());
-            let cell = initial_empty_cells.get(cell_index).unwrap();
-
-            let expand_dir = expand_possibilities[random.gen_range(0..4)];
-            let x_diff = expand_dir[0];
-            let y_diff = expand_dir[1];
-
-            let new_cell_x = cell.0 as i16 + x_diff;
-            let new_cell_y = cell.1 as i16 + y_diff;
-            if !(new_cell_x < 0
-                || new_cell_x >= self.columns as i16
-                || new_cell_y < 0
-                || new_cell_y >= self.rows as i16)
-            {
-                let new_cell = (new_cell_x as u8, new_cell_y as u8);
+            let cell = *initial_empty_cells.get(cell_index).unwrap();
 
-                self.board[new_cell.0 as usize][new_cell.1 as usize].value = CellValue::Empty;
+            let expand_possibilities = self.expansion_offsets(cell);
+            let expand_dir = expand_possibilities[random.gen_range(0..expand_possibilities.len())];
 
-                if !initial_empty_cells.contains(&new_cell) {
+            if let Some(new_cell) = self.translate(cell, expand_dir) {
+                if seen.insert(new_cell) {
                     initial_empty_cells.push(new_cell);
                 }
             }
         }
 
-        let initial_empty_cells = HashSet::<(u8, u8)>::from_iter(initial_empty_cells.into_iter());
-        let mines = self.place_mines(&initial_empty_cells);
+        let mines = self.place_mines(&seen, random);
         self.place_numbers(mines);
         self.propagate_dig(start_cell);
     }
 
     /// Place the mines, avoiding the calculated initial empty area.
     ///
+    /// The mines are sampled from the cells outside of the empty area,
+    ///  so it doesn't matter how dense the board is.
+    ///
     /// The mines coordinates are returned to be used on the
     ///  [place_numbers](Board::place_numbers) method.
     ///
     /// TODO bug: a mine with mines on all fours straight directions
     ///  can happen
-    fn place_mines(&mut self, initial_empty_cells: &HashSet<(u8, u8)>) -> HashSet<(u8, u8)> {
-        let mut mines = HashSet::with_capacity(self.mines_count as usize);
-
-        let mut current_mines = 0;
-
-        let mut random = rand::thread_rng();
-        while current_mines < mines.capacity() {
-            let row = random.gen_range(0..self.rows) as u8;
-            let column = random.gen_range(0..self.columns) as u8;
+    fn place_mines<R: Rng + ?Sized>(
+        &mut self,
+        initial_empty_cells: &HashSet<(u16, u16)>,
+        random: &mut R,
+    ) -> HashSet<(u16, u16)> {
+        let candidates = self
+            .cells()
+            .filter(|cell| !initial_empty_cells.contains(cell))
+            .collect::<Vec<(u16, u16)>>();
 
-            let m = (row, column);
-
-            if mines.contains(&m) || initial_empty_cells.contains(&m) {
-                continue;
-            }
+        let mut mines = HashSet::with_capacity(self.mines_count as usize);
 
-            mines.insert(m);
+        for index in sample(random, candidates.len(), self.mines_count as usize) {
+            let (row, column) = candidates[index];
 
-            current_mines += 1;
+            mines.insert((row, column));
 
             self.board[row as usize][column as usize].value = CellValue::Mine;
             self.cells_to_dig -= 1;
@@ -221,15 +425,15 @@ impl Board {
 
     /// Go over all mines and turn their values into
     ///  [numbers](CellValue::Number) incrementing their count.
-    fn place_numbers(&mut self, mines: HashSet<(u8, u8)>) {
+    fn place_numbers(&mut self, mines: HashSet<(u16, u16)>) {
         for mine in mines.iter() {
             for cell in self
-                .generate_ring(*mine)
-                .filter(|cell| !mines.contains(&(cell.0 as u8, cell.1 as u8)))
-                .collect::<Vec<(i16, i16)>>()
+                .generate_counting(*mine)
+                .filter(|cell| !mines.contains(cell))
+                .collect::<Vec<(u16, u16)>>()
             {
                 match self.board[cell.0 as usize][cell.1 as usize].value {
-                    CellValue::Number(ref mut n) => *n = *n + 1,
+                    CellValue::Number(ref mut n) => *n += 1,
                     _ => self.board[cell.0 as usize][cell.1 as usize].value = CellValue::Number(1),
                 }
             }
@@ -238,7 +442,11 @@ impl Board {
 
     /// Method called for the [Dig](crate::input::plays::PlayMode::Dig) play.
     ///
-    /// If the target cell is already dug or marked nothing happens.
+    /// If the target cell is marked or is an already dug empty
+    ///  cell nothing happens.
+    ///
+    /// If the target is an already dug number, it's a
+    ///  [chord](Board::chord).
     ///
     /// If the target is a mine the game ends and the user loses.
     ///
@@ -250,8 +458,23 @@ impl Board {
     ///
     /// If there is no more cell to dig, the game ends and the
     ///  user wins.
-    pub fn dig(&mut self, play: (u8, u8)) -> GameResult {
-        let mut cell = &mut self.board[play.0 as usize][play.1 as usize];
+    ///
+    /// After the game ends or on [blocked](Board::is_blocked) cells nothing happens.
+    pub fn dig(&mut self, play: (u16, u16)) -> GameResult {
+        return self.step(PlayMode::Dig, |board| board.dig_cell(play));
+    }
+
+    /// Auxiliary method of [dig](Board::dig) that does the actual work.
+    fn dig_cell(&mut self, play: (u16, u16)) -> GameResult {
+        if self.result() != GameResult::Continue || self.is_blocked(play) {
+            return self.result();
+        }
+
+        let cell = &self.board[play.0 as usize][play.1 as usize];
+        if cell.dug && cell.value.is_number() {
+            return self.chord_cell(play);
+        }
+
         if !cell.dug && !cell.marked {
             match cell.value {
                 CellValue::Mine => {
@@ -260,18 +483,21 @@ impl Board {
                 }
                 CellValue::Number(_) => {
                     self.cells_to_dig -= 1;
-                    cell.dug = true
+                    self.set_state(play, true, false);
                 }
                 CellValue::Empty => self.propagate_dig(play),
             }
         }
 
         if self.cells_to_dig == 0 {
-            for row in self.board.iter_mut() {
-                row.iter_mut()
-                    .filter(|cell| cell.value == CellValue::Mine)
-                    .for_each(|cell| cell.marked = true);
+            for (row, col) in self.cells().collect::<Vec<(u16, u16)>>() {
+                let cell = &self.board[row as usize][col as usize];
+                if cell.value == CellValue::Mine && !cell.marked {
+                    self.set_state((row, col), false, true);
+                }
             }
+            // only undug cells can be marked, so only mines are marked now
+            self.marked_count = self.mines_count;
 
             return GameResult::Won;
         }
@@ -279,109 +505,779 @@ impl Board {
         return GameResult::Continue;
     }
 
-    /// Auxiliary method to generate all adjacent cells of a specific cell.
-    fn generate_ring(&self, (row, col): (u8, u8)) -> impl Iterator<Item = (i16, i16)> + '_ {
-        return (-1..=1)
-            .chain(-1..=1)
-            .combinations_with_replacement(2)
-            .unique()
-            .map(move |v| (row as i16 + v[0], col as i16 + v[1]))
-            .filter(move |(r, c)| {
-                *r >= 0 && *r < self.rows as i16 && *c >= 0 && *c < self.columns as i16
-            });
+    /// Method called for the [Chord](crate::input::plays::PlayMode::Chord) play.
+    ///
+    /// If the target is a dug number with as many marked neighbours
+    ///  as its number, all the other undug neighbours are dug.
+    ///  Otherwise nothing happens.
+    ///
+    /// If a mark was wrong, one of the dug neighbours is a mine
+    ///  and the user loses.
+    pub fn chord(&mut self, play: (u16, u16)) -> GameResult {
+        return self.step(PlayMode::Chord, |board| board.chord_cell(play));
+    }
+
+    /// Auxiliary method of [chord](Board::chord) that does the actual work.
+    fn chord_cell(&mut self, play: (u16, u16)) -> GameResult {
+        if self.result() != GameResult::Continue {
+            return self.result();
+        }
+
+        let cell = &self.board[play.0 as usize][play.1 as usize];
+        let n = match cell.value {
+            CellValue::Number(n) if cell.dug => n,
+            _ => return GameResult::Continue,
+        };
+
+        let neighbours = self.neighbours(play);
+        let marked = neighbours
+            .iter()
+            .filter(|(r, c)| self.board[*r as usize][*c as usize].marked)
+            .count();
+        if marked != n as usize {
+            return GameResult::Continue;
+        }
+
+        let mut result = GameResult::Continue;
+        for (r, c) in neighbours {
+            let cell = &self.board[r as usize][c as usize];
+            if cell.dug || cell.marked {
+                continue;
+            }
+
+            result = self.dig_cell((r, c));
+            if result != GameResult::Continue {
+                break;
+            }
+        }
+
+        return result;
+    }
+
+    /// Current state of the game.
+    pub fn result(&self) -> GameResult {
+        if self.deadly_mine.is_some() {
+            return GameResult::Lost;
+        }
+        if self.cells_to_dig == 0 {
+            return GameResult::Won;
+        }
+        return GameResult::Continue;
+    }
+
+    /// Auxiliary method to apply a play, storing everything it changes
+    ///  on the history so it can be undone.
+    ///
+    /// Plays applied inside other plays (ex: the digs of a chord)
+    ///  are part of the outer play.
+    ///
+    /// Plays done while the game is running are counted as clicks
+    ///  and the timer stops when the game ends.
+    fn step<T, F: FnOnce(&mut Board) -> T>(&mut self, mode: PlayMode, play: F) -> T {
+        if self.changes.is_some() {
+            return play(self);
+        }
+        let running = self.result() == GameResult::Continue;
+
+        let before = self.counters();
+        self.changes = Some(Vec::new());
+
+        let result = play(self);
+
+        let changes = self
+            .changes
+            .take()
+            .unwrap()
+            .into_iter()
+            .map(|(cell, before)| {
+                let current = &self.board[cell.0 as usize][cell.1 as usize];
+                Change {
+                    cell,
+                    before,
+                    after: CellState {
+                        dug: current.dug,
+                        marked: current.marked,
+                    },
+                }
+            })
+            .collect();
+        let step = Step {
+            changes,
+            before,
+            after: self.counters(),
+        };
+        if running {
+            self.clicks.count(mode, !step.is_empty());
+        }
+        if !step.is_empty() {
+            self.history.push(step);
+        }
+        self.update_timer();
+
+        return result;
+    }
+
+    /// Auxiliary method to change the state of a cell during a play.
+    fn set_state(&mut self, (row, col): (u16, u16), dug: bool, marked: bool) {
+        let cell = &mut self.board[row as usize][col as usize];
+        if let Some(changes) = self.changes.as_mut() {
+            changes.push((
+                (row, col),
+                CellState {
+                    dug: cell.dug,
+                    marked: cell.marked,
+                },
+            ));
+        }
+        cell.dug = dug;
+        cell.marked = marked;
+    }
+
+    /// Auxiliary method to get the counters changed by plays.
+    fn counters(&self) -> Counters {
+        return Counters {
+            cells_to_dig: self.cells_to_dig,
+            marked_count: self.marked_count,
+            deadly_mine: self.deadly_mine,
+        };
+    }
+
+    /// Auxiliary method to restore the counters changed by plays.
+    fn restore(&mut self, counters: Counters) {
+        self.cells_to_dig = counters.cells_to_dig;
+        self.marked_count = counters.marked_count;
+        self.deadly_mine = counters.deadly_mine;
+    }
+
+    /// Reverts the last play (or redone play).
+    ///
+    /// After the game ends, undo is only possible on practice mode
+    ///  and the game becomes a [practice](Board::is_practice) one.
+    ///
+    /// Returns if a play was undone.
+    pub fn undo(&mut self) -> bool {
+        let game_over = self.result() != GameResult::Continue;
+        if game_over && !self.practice {
+            return false;
+        }
+
+        let step = match self.history.undo() {
+            Some(step) => step.clone(),
+            None => return false,
+        };
+        for change in step.changes.iter().rev() {
+            let cell = &mut self.board[change.cell.0 as usize][change.cell.1 as usize];
+            cell.dug = change.before.dug;
+            cell.marked = change.before.marked;
+        }
+        self.restore(step.before);
+
+        if game_over {
+            self.undid_game_over = true;
+            self.resume();
+        }
+        return true;
+    }
+
+    /// Applies again the last undone play.
+    ///
+    /// Returns if a play was redone.
+    pub fn redo(&mut self) -> bool {
+        let step = match self.history.redo() {
+            Some(step) => step.clone(),
+            None => return false,
+        };
+        for change in step.changes.iter() {
+            let cell = &mut self.board[change.cell.0 as usize][change.cell.1 as usize];
+            cell.dug = change.after.dug;
+            cell.marked = change.after.marked;
+        }
+        self.restore(step.after);
+        self.update_timer();
+        return true;
+    }
+
+    /// If undo was used after the game ended.
+    ///
+    /// The result of such a game must not count toward records.
+    pub fn is_practice(&self) -> bool {
+        return self.undid_game_over;
+    }
+
+    /// If undo can be used after the game ends.
+    pub fn practice_mode(&self) -> bool {
+        return self.practice;
+    }
+
+    /// Auxiliary method to start counting the time of a new playing session.
+    fn resume(&mut self) {
+        self.started = Some(Instant::now());
+    }
+
+    /// Auxiliary method to stop the timer if the game ended.
+    fn update_timer(&mut self) {
+        if self.result() != GameResult::Continue && self.started.is_some() {
+            self.elapsed_before = self.elapsed();
+            self.started = None;
+        }
+    }
+
+    /// Time played so far, including the time before the game was saved.
+    ///
+    /// Starts on the first play and stops when the game ends.
+    pub fn elapsed(&self) -> Duration {
+        return match self.started {
+            Some(started) => self.elapsed_before + started.elapsed(),
+            None => self.elapsed_before,
+        };
+    }
+
+    /// Auxiliary method to generate all adjacent cells of a specific cell,
+    ///  as given by the [topology](Board::topology) of the board or
+    ///  its [neighbourhood](Board::neighbourhood).
+    ///
+    /// On boards with layers, the cells on the same row and column
+    ///  of the layers above and below and their neighbours are also
+    ///  adjacent, up to 26 cells on square boards.
+    fn generate_ring(&self, cell: (u16, u16)) -> impl Iterator<Item = (u16, u16)> + '_ {
+        return self.generate_around(cell, 1);
+    }
+
+    /// Auxiliary method to generate the cells whose numbers
+    ///  count a mine on a specific cell.
+    ///
+    /// The same as the [ring](Board::generate_ring), except for
+    ///  neighbourhoods that aren't symmetric, ex: only the cells above.
+    fn generate_counting(&self, cell: (u16, u16)) -> impl Iterator<Item = (u16, u16)> + '_ {
+        let sign = match self.neighbourhood {
+            Some(_) => -1,
+            None => 1,
+        };
+        return self.generate_around(cell, sign);
+    }
+
+    /// Auxiliary method of [generate_ring](Board::generate_ring) and
+    ///  [generate_counting](Board::generate_counting), with the offsets
+    ///  multiplied by the sign.
+    fn generate_around(
+        &self,
+        cell: (u16, u16),
+        sign: i32,
+    ) -> impl Iterator<Item = (u16, u16)> + '_ {
+        let offsets = match &self.neighbourhood {
+            Some(neighbourhood) => neighbourhood.offsets(),
+            None => self.topology.neighbour_offsets(cell.0 % self.rows),
+        };
+        let layers: &[i32] = match self.layers {
+            1 => &[],
+            _ => &[-1, 1],
+        };
+
+        let same_layer = offsets.iter().map(|&(r, c)| (0, r, c));
+        let other_layers = layers.iter().flat_map(move |&l| {
+            std::iter::once((l, 0, 0)).chain(offsets.iter().map(move |&(r, c)| (l, r, c)))
+        });
+        return same_layer
+            .chain(other_layers)
+            .filter_map(move |(l, r, c)| self.translate(cell, (sign * l, sign * r, sign * c)));
+    }
+
+    /// Auxiliary method to get the directions the empty area of the
+    ///  first play can grow from a cell, as layer, row and column differences.
+    ///
+    /// On boards with layers it can also grow to the layers above and below.
+    fn expansion_offsets(&self, cell: (u16, u16)) -> Vec<(i32, i32, i32)> {
+        let offsets = self.topology.expansion_offsets(cell.0 % self.rows);
+        let mut expansion = offsets
+            .iter()
+            .map(|&(r, c)| (0, r, c))
+            .collect::<Vec<(i32, i32, i32)>>();
+        if self.layers > 1 {
+            expansion.extend([(1, 0, 0), (-1, 0, 0)]);
+        }
+        return expansion;
+    }
+
+    /// Auxiliary method of [place_layout](Board::place_layout) to count the
+    ///  cells the empty area can grow to from the given ones, up to a limit.
+    fn reachable(&self, from: &[(u16, u16)], limit: usize) -> usize {
+        let mut seen = from.iter().copied().collect::<HashSet<(u16, u16)>>();
+        let mut to_visit = from.to_vec();
+        while let Some(cell) = to_visit.pop() {
+            if seen.len() >= limit {
+                break;
+            }
+            for offset in self.expansion_offsets(cell) {
+                if let Some(next) = self.translate(cell, offset) {
+                    if seen.insert(next) {
+                        to_visit.push(next);
+                    }
+                }
+            }
+        }
+        return seen.len();
+    }
+
+    /// Auxiliary method to get the cell at a layer, row and column
+    ///  difference from another, `None` if it's outside of the board
+    ///  or [blocked](Board::is_blocked).
+    ///
+    /// If the board [wraps](Board::wraps) the cells outside of its rows
+    ///  and columns are the ones on the opposite edge of the layer.
+    fn translate(&self, cell: (u16, u16), (l, r, c): (i32, i32, i32)) -> Option<(u16, u16)> {
+        let (row, col, layer) = self.position(cell);
+        let (mut row, mut col, layer) = (row as i32 + r, col as i32 + c, layer as i32 + l);
+        if self.wrap {
+            row = row.rem_euclid(self.rows as i32);
+            col = col.rem_euclid(self.columns as i32);
+        }
+        if row < 0 || row >= self.rows as i32 || col < 0 || col >= self.columns as i32 {
+            return None;
+        }
+        if layer < 0 || layer >= self.layers as i32 {
+            return None;
+        }
+        let cell = ((layer * self.rows as i32 + row) as u16, col as u16);
+        if self.is_blocked(cell) {
+            return None;
+        }
+        return Some(cell);
+    }
+
+    /// Auxiliary method to get the total number of cells
+    ///  that can be played, on all the layers.
+    fn size(&self) -> u32 {
+        let layer = match &self.mask {
+            Some(mask) => mask.playable(),
+            None => self.rows as u32 * self.columns as u32,
+        };
+        return layer * self.layers as u32;
     }
 
     /// Method to show all cells around a cell with value [Empty](CellValue::Empty).
     ///
     /// If its neighbours are also cell with [Empty](CellValue::Empty) it propagates
     ///  the effect.
-    fn propagate_dig(&mut self, (initial_row, initial_col): (u8, u8)) {
-        let mut to_propagate = Vec::new();
-        to_propagate.push((initial_row as i16, initial_col as i16));
-
-        let mut seen = HashSet::<(i16, i16)>::new();
+    fn propagate_dig(&mut self, initial_cell: (u16, u16)) {
+        let mut to_propagate = vec![initial_cell];
 
         while let Some((r, c)) = to_propagate.pop() {
-            let cell = &mut self.board[r as usize][c as usize];
-            cell.dug = true;
-            cell.marked = false;
-            self.cells_to_dig -= 1;
+            let cell = &self.board[r as usize][c as usize];
 
-            seen.insert((r as i16, c as i16));
+            // the same cell can be pushed by several neighbours
+            //  before being visited
+            if cell.dug {
+                continue;
+            }
+
+            if cell.marked {
+                self.marked_count -= 1;
+            }
+            let is_number = cell.value.is_number();
+            self.set_state((r, c), true, false);
+            self.cells_to_dig -= 1;
 
-            if cell.value.is_number() {
+            if is_number {
                 continue;
             }
 
             to_propagate.extend(
-                self.generate_ring((r as u8, c as u8))
+                self.generate_ring((r, c))
                     .filter(|(r, c)| self.board[*r as usize][*c as usize].value != CellValue::Mine)
-                    .filter(|(r, c)| !self.board[*r as usize][*c as usize].dug)
-                    .filter(|cell| !seen.contains(cell)),
+                    .filter(|(r, c)| !self.board[*r as usize][*c as usize].dug),
             )
         }
     }
 
     /// Method called for the [Mark](crate::input::plays::PlayMode::Mark) play.
     ///
-    /// If the the cell is not dug nothing happens.
-    pub fn mark(&mut self, (row, col): (u8, u8)) {
-        let cell = &mut self.board[row as usize][col as usize];
-        if !cell.dug {
-            cell.marked = !(cell.marked ^ cell.marked)
+    /// Marks the cell as a mine. If the cell is dug, already
+    ///  marked, [blocked](Board::is_blocked) or there are no
+    ///  [flags left](Board::flags_left) nothing happens.
+    ///
+    /// Returns if the cell was marked.
+    pub fn mark(&mut self, cell: (u16, u16)) -> bool {
+        return self.step(PlayMode::Mark, |board| board.mark_cell(cell));
+    }
+
+    /// Auxiliary method of [mark](Board::mark) that does the actual work.
+    fn mark_cell(&mut self, (row, col): (u16, u16)) -> bool {
+        if self.result() != GameResult::Continue || self.flags_left() == Some(0) {
+            return false;
+        }
+        if self.is_blocked((row, col)) {
+            return false;
         }
+
+        let cell = &self.board[row as usize][col as usize];
+        if cell.dug || cell.marked {
+            return false;
+        }
+
+        self.set_state((row, col), false, true);
+        self.marked_count += 1;
+        return true;
     }
 
     /// Method called for the [Unmark](crate::input::plays::PlayMode::Unmark) play.
     ///
-    /// If the the cell is not dug nothing happens.
-    pub fn unmark(&mut self, (row, col): (u8, u8)) {
-        let cell = &mut self.board[row as usize][col as usize];
+    /// Removes the mark of the cell. If the cell is not
+    ///  marked nothing happens.
+    ///
+    /// Returns if the cell was unmarked.
+    pub fn unmark(&mut self, cell: (u16, u16)) -> bool {
+        return self.step(PlayMode::Unmark, |board| board.unmark_cell(cell));
+    }
+
+    /// Auxiliary method of [unmark](Board::unmark) that does the actual work.
+    fn unmark_cell(&mut self, (row, col): (u16, u16)) -> bool {
+        if self.result() != GameResult::Continue {
+            return false;
+        }
+
+        let cell = &self.board[row as usize][col as usize];
+        if !cell.marked {
+            return false;
+        }
+
+        self.set_state((row, col), false, false);
+        self.marked_count -= 1;
+        return true;
+    }
+
+    /// Number of mines minus the number of marked cells.
+    ///
+    /// Can be negative if the user marks more cells than there are mines.
+    pub fn mines_left(&self) -> i64 {
+        return self.mines_count as i64 - self.marked_count as i64;
+    }
+
+    /// Number of marked cells getter
+    pub fn marked_count(&self) -> u32 {
+        return self.marked_count;
+    }
+
+    /// How many more cells can be marked.
+    ///
+    /// `None` if there is no limit.
+    pub fn flags_left(&self) -> Option<u32> {
+        return self
+            .flag_limit
+            .map(|limit| limit.saturating_sub(self.marked_count));
+    }
+
+    /// Number of rows of each layer getter
+    pub fn rows(&self) -> u16 {
+        return self.rows;
+    }
+
+    /// Number of columns getter
+    pub fn columns(&self) -> u16 {
+        return self.columns;
+    }
+
+    /// Number of layers getter, 1 on flat boards
+    pub fn layers(&self) -> u16 {
+        return self.layers;
+    }
+
+    /// Number of mines getter
+    pub fn mines_count(&self) -> u32 {
+        return self.mines_count;
+    }
+
+    /// Topology getter
+    pub fn topology(&self) -> Topology {
+        return self.topology;
+    }
+
+    /// Cells counted by the numbers, if they aren't the
+    ///  neighbours of the [topology](Board::topology).
+    pub fn neighbourhood(&self) -> Option<&Neighbourhood> {
+        return self.neighbourhood.as_ref();
+    }
+
+    /// If the opposite edges of the board are next to each other.
+    pub fn wraps(&self) -> bool {
+        return self.wrap;
+    }
+
+    /// Cells that can't be played, `None` if all can.
+    pub fn mask(&self) -> Option<&Mask> {
+        return self.mask.as_ref();
+    }
+
+    /// If the cell can't be played because of the [mask](Board::mask).
+    pub fn is_blocked(&self, cell: (u16, u16)) -> bool {
+        return match &self.mask {
+            Some(mask) => {
+                let (row, col, _) = self.position(cell);
+                mask.is_blocked(row, col)
+            }
+            None => false,
+        };
+    }
+
+    /// Seed getter
+    ///
+    /// `None` if the board was built with
+    ///  [build_with_rng](Board::build_with_rng).
+    pub fn seed(&self) -> Option<u64> {
+        return self.seed;
+    }
+
+    /// Number of boards generated on the last [build](Board::build).
+    ///
+    /// Always 1 if not on no-guess mode.
+    pub fn generation_attempts(&self) -> u32 {
+        return self.attempts;
+    }
+
+    /// If the board was verified to be solvable without guessing.
+    ///
+    /// Only on no-guess mode, it's `false` if no such
+    ///  board was found within the attempts.
+    pub fn is_guess_free(&self) -> bool {
+        return self.guess_free;
+    }
+
+    /// Remaining cells to dig getter, blocked cells are never counted
+    pub fn cells_to_dig(&self) -> u32 {
+        return self.cells_to_dig;
+    }
+
+    /// If the coordinates are inside the board.
+    pub fn contains(&self, (row, col): (u16, u16)) -> bool {
+        return (row as u32) < self.rows as u32 * self.layers as u32 && col < self.columns;
+    }
+
+    /// All the cells of the board that can be played, row by row and layer by layer.
+    pub fn cells(&self) -> impl Iterator<Item = (u16, u16)> + '_ {
+        return (0..self.rows * self.layers)
+            .cartesian_product(0..self.columns)
+            .filter(move |&cell| !self.is_blocked(cell));
+    }
+
+    /// Row on its layer, column and layer of a cell.
+    pub fn position(&self, (row, col): (u16, u16)) -> (u16, u16, u16) {
+        return (row % self.rows, col, row / self.rows);
+    }
+
+    /// All the cells whose mines are counted on the number of a cell.
+    pub fn neighbours(&self, cell: (u16, u16)) -> Vec<(u16, u16)> {
+        return self.generate_ring(cell).collect();
+    }
+
+    /// Characters taken by the biggest number a cell can have,
+    ///  so all the cells can be drawn with the same width.
+    ///
+    /// The biggest number is the count of neighbours of a cell on
+    ///  the middle of the board, ex: 26 on boards with 3 layers.
+    pub fn cell_width(&self) -> usize {
+        let offsets = match &self.neighbourhood {
+            Some(neighbourhood) => neighbourhood.offsets().len(),
+            None => self.topology.neighbour_offsets(0).len(),
+        };
+        // the layer of the cell and the ones next to it
+        let layers = self.layers.min(3) as usize;
+        let neighbours = offsets * layers + layers - 1;
+        return neighbours.to_string().len();
+    }
+
+    /// Query what the player can see of a cell.
+    ///
+    /// The value of a cell is only exposed after it is dug,
+    ///  so it's safe to use this to build bots or other frontends.
+    pub fn cell_view(&self, (row, col): (u16, u16)) -> CellView {
+        if self.is_blocked((row, col)) {
+            return CellView::Blocked;
+        }
+        let cell = &self.board[row as usize][col as usize];
+        if cell.marked {
+            return CellView::Marked;
+        }
+        if self.deadly_mine == Some((row, col)) {
+            return CellView::Mine;
+        }
         if !cell.dug {
-            cell.marked ^= cell.marked
-        }
-    }
-
-    /// Method called when the game ends to print a compact version of the board.
-    pub fn finish(&self) {
-        for (i, row) in enumerate(&self.board) {
-            for (j, cell) in enumerate(row) {
-                if !cell.marked {
-                    match cell.value {
-                        CellValue::Mine => match self.deadly_mine {
-                            Some(deadly_mine) if deadly_mine == (i as u8, j as u8) => {
-                                print!("{}", ansi_term::Color::Red.paint("O"))
-                            }
-                            _ => print!("{}", ansi_term::Color::Yellow.paint("O")),
-                        },
-                        v if cell.dug => print!("{}", v),
-                        _ => print!("?"),
-                    }
-                } else {
-                    match cell.value {
-                        CellValue::Mine => print!("{}", ansi_term::Color::Green.paint("X")),
-                        _ => print!("{}", ansi_term::Color::Red.paint("X")),
+            return CellView::Hidden;
+        }
+        return match cell.value {
+            CellValue::Empty => CellView::Empty,
+            CellValue::Number(n) => CellView::Number(n),
+            CellValue::Mine => CellView::Mine,
+        };
+    }
+
+    /// Statistics of the game so far.
+    pub fn summary(&self) -> Summary {
+        let safe_cells = self.size() - self.mines_count;
+        return Summary::new(
+            self.result(),
+            self.elapsed(),
+            self.clicks,
+            safe_cells - self.cells_to_dig,
+            self.benchmark(),
+        );
+    }
+
+    /// Method called when the game ends to print a compact version
+    ///  of the board and the [summary](Board::summary) of the game.
+    ///
+    /// Mines are shown as `O`, the dug one as `*`, marks as `X`
+    ///  if they are over a mine or `!` if they aren't, and
+    ///  blocked cells as `#`. Boards with layers show them side by side.
+    ///
+    /// The seed is also printed so the board can be played again.
+    pub fn finish(&self) -> Summary {
+        let mut width = self.cell_width();
+        if self.topology == Topology::Hexagonal && width.is_multiple_of(2) {
+            // so half a column is a whole number of characters
+            width += 1;
+        }
+        for row in 0..self.rows {
+            let line = (0..self.layers)
+                .map(|layer| {
+                    let mut line = self.compact_row(layer * self.rows + row, width);
+                    // so the next layer starts on the same column on every row
+                    if self.layers > 1
+                        && self.topology == Topology::Hexagonal
+                        && !self.topology.is_shifted(row)
+                    {
+                        line.push_str(&" ".repeat(width.div_ceil(2)));
                     }
+                    line
+                })
+                .join(LAYER_GAP);
+            println!("{}", line);
+        }
+
+        if let Some(seed) = self.seed {
+            println!("seed: {}", seed);
+        }
+
+        let summary = self.summary();
+        println!("{}", summary);
+        return summary;
+    }
+
+    /// Auxiliary method of [finish](Board::finish) to draw a row.
+    fn compact_row(&self, i: u16, width: usize) -> String {
+        let theme = theme::current();
+        let row = &self.board[i as usize];
+        let mut line = String::new();
+        if self.topology.is_shifted(i % self.rows) {
+            line.push_str(&" ".repeat(width.div_ceil(2)));
+        }
+        for (j, cell) in enumerate(row) {
+            line.push_str(&" ".repeat(width - cell.width()));
+            let symbol = if self.is_blocked((i, j as u16)) {
+                BLOCKED.to_string()
+            } else if !cell.marked {
+                match cell.value {
+                    CellValue::Mine => match self.deadly_mine {
+                        Some(deadly_mine) if deadly_mine == (i, j as u16) => {
+                            theme.paint(Role::DeadlyMine, "*")
+                        }
+                        _ => theme.paint(Role::Mine, "O"),
+                    },
+                    v if cell.dug => v.to_string(),
+                    _ => "?".to_string(),
                 }
-                if j != row.len() - 1 {
-                    print!(" ");
+            } else {
+                match cell.value {
+                    CellValue::Mine => theme.paint(Role::CorrectFlag, "X"),
+                    _ => theme.paint(Role::WrongFlag, "!"),
                 }
+            };
+            line.push_str(&symbol);
+            if j != row.len() - 1 {
+                line.push(' ');
             }
-            println!();
         }
+        return line;
+    }
+
+    /// Auxiliary method of [fmt](Board::fmt) to draw a layer, line by line.
+    ///
+    /// On boards with several layers the first line is the number of
+    ///  the layer, and all the lines take the same width, so the next
+    ///  layer can be drawn on their right.
+    fn draw_layer(&self, layer: u16, width: usize) -> Vec<String> {
+        let shift = width.div_ceil(2);
+
+        // the cells plus the space after each one, and the shift
+        let cells_width = self.columns as usize * (width + 1)
+            + match self.topology {
+                Topology::Hexagonal => shift,
+                Topology::Square => 0,
+            };
+        let (edge, wrap_bar) = match self.wrap {
+            true => ('~', "~".repeat(cells_width + 2)),
+            false => (' ', String::new()),
+        };
+        // the coordinates, the edges and the cells
+        let (line_width, right_width) = match self.layers {
+            1 => (0, 0),
+            _ => (2 * width + 2 + cells_width, width),
+        };
+        let pad = |line: String| format!("{:1$}", line, line_width);
+
+        let mut lines = Vec::new();
+        if self.layers > 1 {
+            lines.push(pad(format!("layer {}", layer)));
+        }
+        lines.push(pad(horizontal_bar(self.columns, width)));
+        if self.wrap {
+            lines.push(pad(format!("{:1$}{2}", "", width, wrap_bar)));
+        }
+
+        for i in 0..self.rows {
+            let shifted = self.topology.is_shifted(i);
+            // auxiliary bar on the left of coordinates
+            let mut line = format!("{:1$}{2}", i, width, edge);
+            if shifted {
+                line.push_str(&" ".repeat(shift));
+            }
+            let row = layer * self.rows + i;
+            for (j, cell) in enumerate(&self.board[row as usize]) {
+                match self.is_blocked((row, j as u16)) {
+                    true => line.push_str(&format!("{:1$}{2} ", "", width - 1, BLOCKED)),
+                    false => line.push_str(&format!("{:1$}{2} ", "", width - cell.width(), cell)),
+                }
+            }
+            if self.topology == Topology::Hexagonal && !shifted {
+                line.push_str(&" ".repeat(shift));
+            }
+            // auxiliary bar on the right of coordinates
+            line.push_str(&format!("{}{:<2$}", edge, i, right_width));
+            lines.push(line);
+        }
+
+        if self.wrap {
+            lines.push(pad(format!("{:1$}{2}", "", width, wrap_bar)));
+        }
+        lines.push(pad(horizontal_bar(self.columns, width)));
+        return lines;
     }
 }
 
-/// Auxiliary function to print horizontal bars above and under the board
+/// Space between the layers of a board drawn side by side
+const LAYER_GAP: &str = "  ";
+
+/// Symbol of the cells [blocked](Board::is_blocked) by the mask
+pub const BLOCKED: char = '#';
+
+/// Auxiliary function to draw horizontal bars above and under the board
 ///  so its easier to know the coordinates of a cell.
-fn print_horizontal_bar(f: &mut Formatter<'_>, columns: i32) -> fmt::Result {
-    write!(f, "   ")?;
+///
+/// Each column takes `width + 1` characters.
+fn horizontal_bar(columns: u16, width: usize) -> String {
+    let mut bar = format!("{:1$}", "", width + 1);
     for i in 0..columns {
-        write!(f, "{:2} ", i)?;
+        bar.push_str(&format!("{:1$} ", i, width));
     }
-    writeln!(f)?;
-
-    Ok(())
+    return bar;
 }
 
 impl fmt::Display for Board {
@@ -389,20 +1285,291 @@ impl fmt::Display for Board {
     ///
     /// Prints auxiliary numbers around the board so its easier
     ///  for the user to know the coordinates of a cell.
+    ///
+    /// The columns get wider on big boards so the coordinates still fit.
+    ///
+    /// On [hexagonal](Topology::Hexagonal) boards the shifted rows
+    ///  start half a column to the right.
+    ///
+    /// If the board [wraps](Board::wraps) its edges are drawn with `~`.
+    ///
+    /// [Blocked](Board::is_blocked) cells are drawn with `#`.
+    ///
+    /// Boards with layers show them side by side, each with its number above.
+    ///
+    /// The number of mines left to mark and the time played
+    ///  are shown above the board.
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        print_horizontal_bar(f, self.columns as i32)?;
+        writeln!(
+            f,
+            "mines left: {}  time: {}s",
+            self.mines_left(),
+            self.elapsed().as_secs()
+        )?;
 
-        for (i, row) in enumerate(self.board.iter()) {
-            write!(f, "{:2}  ", i)?;  // auxiliary bar on the left of coordinates
-            for cell in row {
-                cell.fmt(f)?;
-                write!(f, "  ")?;
-            }
-            writeln!(f, "{}", i)?;  // auxiliary bar on the right of coordinates
+        let mut width = (self.rows.max(self.columns) - 1)
+            .to_string()
+            .len()
+            .max(self.cell_width())
+            .max(2);
+        if self.topology == Topology::Hexagonal && width.is_multiple_of(2) {
+            // so half a column is a whole number of characters
+            width += 1;
         }
 
-        print_horizontal_bar(f, self.columns as i32)?;
+        let layers = (0..self.layers)
+            .map(|layer| self.draw_layer(layer, width))
+            .collect::<Vec<Vec<String>>>();
+        for i in 0..layers[0].len() {
+            writeln!(
+                f,
+                "{}",
+                layers.iter().map(|lines| &lines[i]).join(LAYER_GAP)
+            )?;
+        }
 
         Ok(())
     }
 }
+
+/// Auxiliary function for the tests to create a board with the given
+///  grid: `*` for mines, `o` for dug cells, `f` for marked cells, `F`
+///  for marked mines and `.` for the other cells.
+#[cfg(test)]
+pub(crate) fn from_grid(grid: &[&str]) -> Board {
+    let mut board =
+        Board::new(&Parameters::new(grid.len() as u16, grid[0].len() as u16, 0).unwrap());
+    let mut mines = HashSet::new();
+    for (row, line) in enumerate(grid) {
+        for (column, c) in line.chars().enumerate() {
+            let cell = &mut board.board[row][column];
+            if "*F".contains(c) {
+                cell.value = CellValue::Mine;
+                mines.insert((row as u16, column as u16));
+                board.cells_to_dig -= 1;
+            }
+            if c == 'o' {
+                cell.dug = true;
+                board.cells_to_dig -= 1;
+            }
+            if "fF".contains(c) {
+                cell.marked = true;
+                board.marked_count += 1;
+            }
+        }
+    }
+    board.mines_count = mines.len() as u32;
+    board.place_numbers(mines);
+    return board;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Auxiliary function to build a board with a fixed random generator.
+    fn built(params: &Parameters, start_cell: (u16, u16), seed: u64) -> Board {
+        let mut board = Board::new(params);
+        board.build_with_rng(start_cell, &mut StdRng::seed_from_u64(seed));
+        return board;
+    }
+
+    /// Auxiliary function to create a board with the given mines.
+    fn with_mines(params: &Parameters, mines: &[(u16, u16)]) -> Board {
+        let mut board = Board::new(params);
+        for &(row, column) in mines {
+            board.board[row as usize][column as usize].value = CellValue::Mine;
+        }
+        board.cells_to_dig -= mines.len() as u32;
+        board.mines_count = mines.len() as u32;
+        board.place_numbers(mines.iter().copied().collect());
+        return board;
+    }
+
+    /// Auxiliary function to get the mines of a board.
+    fn mines(board: &Board) -> Vec<(u16, u16)> {
+        return (0..board.rows)
+            .cartesian_product(0..board.columns)
+            .filter(|(r, c)| board.board[*r as usize][*c as usize].value == CellValue::Mine)
+            .collect();
+    }
+
+    #[test]
+    fn seeds_give_the_same_layout() {
+        let layout = |seed: u64, start_cell: (u16, u16)| {
+            let mut board = Board::new(&Parameters::new(9, 9, 10).unwrap().with_seed(seed));
+            board.build(start_cell);
+            return mines(&board);
+        };
+
+        assert_eq!(layout(42, (4, 4)), layout(42, (4, 4)));
+        assert_ne!(layout(42, (4, 4)), layout(43, (4, 4)));
+        assert_ne!(layout(42, (4, 4)), layout(42, (1, 2)));
+    }
+
+    #[test]
+    fn first_dig_is_always_safe() {
+        for seed in 0..200 {
+            let start_cell = ((seed % 9) as u16, (seed * 7 % 9) as u16);
+            let mut board = Board::new(&Parameters::new(9, 9, 10).unwrap().with_seed(seed));
+            board.build(start_cell);
+            let value = board.board[start_cell.0 as usize][start_cell.1 as usize].value;
+            assert!(value != CellValue::Mine, "seed {}", seed);
+            assert_eq!(board.dig(start_cell), GameResult::Continue, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn opening_has_room_for_the_neighbourhood_on_all_layers() {
+        let radius = Neighbourhood::preset("radius-2").unwrap();
+        assert!(Parameters::layered(5, 5, 3, 40)
+            .unwrap()
+            .with_neighbourhood(radius.clone())
+            .is_err());
+
+        // as many mines as allowed, so there are no other cells for
+        //  them if the neighbourhood on the middle layer doesn't fit
+        let params = Parameters::layered(9, 9, 3, 0)
+            .unwrap()
+            .with_neighbourhood(radius.clone())
+            .unwrap();
+        let mines = params.cells() - params.opening();
+        let params = Parameters::layered(9, 9, 3, mines)
+            .unwrap()
+            .with_neighbourhood(radius)
+            .unwrap();
+        let board = built(&params, (13, 4), 0);
+        assert_eq!(board.cell_view((13, 4)), CellView::Empty);
+        assert_eq!(board.result(), GameResult::Won);
+    }
+
+    #[test]
+    fn chord_on_a_satisfied_number_digs_the_neighbours() {
+        let mut board = from_grid(&["F...", ".o..", "....", "...."]);
+        assert_eq!(board.chord((1, 1)), GameResult::Won);
+        assert_eq!(board.cell_view((0, 1)), CellView::Number(1));
+        assert_eq!(board.cell_view((3, 3)), CellView::Empty);
+        assert_eq!(board.cells_to_dig(), 0);
+    }
+
+    #[test]
+    fn chord_on_an_unsatisfied_number_does_nothing() {
+        let mut board = from_grid(&["*...", ".o..", "....", "...."]);
+        assert_eq!(board.chord((1, 1)), GameResult::Continue);
+        assert_eq!(board.cell_view((0, 1)), CellView::Hidden);
+        assert_eq!(board.cells_to_dig(), 14);
+    }
+
+    #[test]
+    fn chord_with_a_wrong_mark_loses() {
+        let mut board = from_grid(&["*f..", ".o..", "....", "...."]);
+        assert_eq!(board.chord((1, 1)), GameResult::Lost);
+        assert_eq!(board.cell_view((0, 0)), CellView::Mine);
+    }
+
+    #[test]
+    fn undo_after_losing_restores_the_board_on_practice() {
+        let mut board = from_grid(&["*.", ".."]);
+        board.dig((1, 1));
+        board.dig((0, 0));
+        assert_eq!(board.result(), GameResult::Lost);
+        assert!(!board.clone().undo());
+
+        board.practice = true;
+        assert!(board.undo());
+        assert_eq!(board.result(), GameResult::Continue);
+        assert_eq!(board.cell_view((0, 0)), CellView::Hidden);
+        assert_eq!(board.cell_view((1, 1)), CellView::Number(1));
+        assert_eq!(board.cells_to_dig(), 2);
+        assert!(board.is_practice());
+    }
+
+    #[test]
+    fn redo_is_cleared_by_a_new_play() {
+        let mut board = from_grid(&["*.", ".."]);
+        board.dig((1, 1));
+        assert!(board.undo());
+        assert!(board.redo());
+        assert_eq!(board.cell_view((1, 1)), CellView::Number(1));
+
+        assert!(board.undo());
+        board.dig((1, 0));
+        assert!(!board.redo());
+        assert_eq!(board.cell_view((1, 1)), CellView::Hidden);
+    }
+
+    #[test]
+    fn undo_of_the_first_dig_is_refused() {
+        let mut board = built(&Parameters::new(9, 9, 10).unwrap(), (4, 4), 0);
+        assert!(!board.undo());
+        assert_ne!(board.cell_view((4, 4)), CellView::Hidden);
+    }
+
+    #[test]
+    fn wrapped_corners_have_all_their_neighbours() {
+        let params = Parameters::new(5, 5, 0).unwrap().with_wrap().unwrap();
+        let board = Board::new(&params);
+        let mut neighbours = board.neighbours((0, 0));
+        neighbours.sort_unstable();
+        assert_eq!(
+            neighbours,
+            [
+                (0, 1),
+                (0, 4),
+                (1, 0),
+                (1, 1),
+                (1, 4),
+                (4, 0),
+                (4, 1),
+                (4, 4)
+            ]
+        );
+    }
+
+    #[test]
+    fn digs_spread_across_the_wrapped_edges() {
+        // a column of mines splits the board, except across the edges
+        let wall = (0..5).map(|row| (row, 2)).collect::<Vec<(u16, u16)>>();
+        let params = Parameters::new(5, 5, 5).unwrap().with_wrap().unwrap();
+        let mut board = with_mines(&params, &wall);
+        assert_eq!(board.dig((2, 0)), GameResult::Won);
+        assert_eq!(board.cell_view((2, 4)), CellView::Empty);
+        assert_eq!(board.cell_view((2, 3)), CellView::Number(3));
+    }
+
+    #[test]
+    fn blocked_cells_are_not_dug() {
+        let mask = "xxx/x.x/xxx".parse::<Mask>().unwrap();
+        let params = Parameters::new(3, 3, 1).unwrap().with_mask(mask).unwrap();
+        let mut board = with_mines(&params, &[(0, 0)]);
+        // 9 cells, one blocked and one mine
+        assert_eq!(board.cells_to_dig(), 7);
+        assert_eq!(board.cell_view((1, 1)), CellView::Blocked);
+        assert!(board.is_blocked((1, 1)));
+
+        assert_eq!(board.dig((1, 1)), GameResult::Continue);
+        assert_eq!(board.cells_to_dig(), 7);
+        // the blocked cell doesn't count the mine
+        assert_eq!(board.dig((2, 2)), GameResult::Won);
+        assert_eq!(board.cell_view((0, 1)), CellView::Number(1));
+        assert_eq!(board.cell_view((1, 1)), CellView::Blocked);
+    }
+
+    #[test]
+    fn mines_are_never_placed_on_blocked_cells() {
+        let mask = Mask::preset("ring").unwrap();
+        let params = Parameters::new(11, 11, 0).unwrap().with_mask(mask).unwrap();
+        // as many mines as allowed, so the blocked cells are tempting
+        let most = params.cells() - params.opening();
+        let params = Parameters::new(11, 11, most)
+            .unwrap()
+            .with_mask(Mask::preset("ring").unwrap())
+            .unwrap();
+        for seed in 0..20 {
+            let board = built(&params, (0, 5), seed);
+            let mines = mines(&board);
+            assert_eq!(mines.len() as u32, most);
+            assert!(mines.iter().all(|cell| !board.is_blocked(*cell)));
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Bechtel's Board Benchmark Value (3BV) of a board.
+//!
+//! The 3BV is the minimum number of clicks needed to clear a board
+//!  without chords: one for each opening (a connected area of empty
+//!  cells, which also digs the numbers around it) plus one for each
+//!  number that isn't next to an empty cell (an isolated number).
+//!
+//! The isolated numbers that touch each other form islands.
+//!
+//! The solved 3BV only counts the openings and isolated numbers
+//!  already dug, it's used to rate lost games.
+//!
+
+use std::collections::HashSet;
+
+use crate::board::{Board, CellValue};
+
+/// Structure of the layout of a board.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub struct Benchmark {
+    /// Connected areas of empty cells
+    openings: u32,
+    /// Numbers that aren't next to an empty cell
+    isolated_numbers: u32,
+    /// Connected groups of isolated numbers
+    islands: u32,
+    /// Openings with dug cells
+    solved_openings: u32,
+    /// Dug isolated numbers
+    solved_isolated_numbers: u32,
+}
+
+impl Benchmark {
+    /// Openings getter
+    pub fn openings(&self) -> u32 {
+        return self.openings;
+    }
+
+    /// Isolated numbers getter
+    pub fn isolated_numbers(&self) -> u32 {
+        return self.isolated_numbers;
+    }
+
+    /// Islands getter
+    pub fn islands(&self) -> u32 {
+        return self.islands;
+    }
+
+    /// The 3BV: openings plus isolated numbers
+    pub fn bbbv(&self) -> u32 {
+        return self.openings + self.isolated_numbers;
+    }
+
+    /// The 3BV of the dug cells
+    pub fn solved_bbbv(&self) -> u32 {
+        return self.solved_openings + self.solved_isolated_numbers;
+    }
+}
+
+impl Board {
+    /// Auxiliary method to get the value of a cell.
+    fn value(&self, (row, column): (u16, u16)) -> CellValue {
+        return self.board[row as usize][column as usize].value;
+    }
+
+    /// Auxiliary method to count the connected groups of cells
+    ///  that satisfy the condition, and how many of them have dug cells.
+    ///
+    /// The groups grow towards the cells that count them, which are
+    ///  the ones a cell digs when it is empty on asymmetric neighbourhoods.
+    fn count_groups<F: Fn((u16, u16)) -> bool>(&self, is_part: F) -> (u32, u32) {
+        let mut seen = HashSet::new();
+        let mut groups = 0;
+        let mut dug_groups = 0;
+
+        for start in self.cells() {
+            if !is_part(start) || !seen.insert(start) {
+                continue;
+            }
+            groups += 1;
+
+            let mut dug = false;
+            let mut to_visit = vec![start];
+            while let Some(cell) = to_visit.pop() {
+                dug |= self.board[cell.0 as usize][cell.1 as usize].dug;
+                for neighbour in self.generate_counting(cell) {
+                    if is_part(neighbour) && seen.insert(neighbour) {
+                        to_visit.push(neighbour);
+                    }
+                }
+            }
+            if dug {
+                dug_groups += 1;
+            }
+        }
+
+        return (groups, dug_groups);
+    }
+
+    /// Computes the [3BV](crate::board::benchmark) of the board.
+    ///
+    /// Before the board is built there are no mines,
+    ///  so the whole board is a single opening.
+    pub fn benchmark(&self) -> Benchmark {
+        let is_isolated = |cell: (u16, u16)| {
+            self.value(cell).is_number()
+                && self
+                    .generate_counting(cell)
+                    .all(|neighbour| self.value(neighbour) != CellValue::Empty)
+        };
+
+        let mut isolated_numbers = 0;
+        let mut solved_isolated_numbers = 0;
+        for (row, column) in self.cells() {
+            if is_isolated((row, column)) {
+                isolated_numbers += 1;
+                if self.board[row as usize][column as usize].dug {
+                    solved_isolated_numbers += 1;
+                }
+            }
+        }
+
+        let (openings, solved_openings) =
+            self.count_groups(|cell| self.value(cell) == CellValue::Empty);
+        let (islands, _) = self.count_groups(is_isolated);
+
+        return Benchmark {
+            openings,
+            isolated_numbers,
+            islands,
+            solved_openings,
+            solved_isolated_numbers,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::save::grid_save;
+    use crate::board::Board;
+
+    #[test]
+    fn asymmetric_neighbourhoods_follow_the_counting_cells() {
+        // the numbers count the cell above, so each empty column is an
+        //  opening and the number is dug by the empty cell below it
+        let save =
+            grid_save(&["*..", "...", "..."]).replace("neighbourhood none", "neighbourhood -1,0");
+        let board = Board::read_save(save.as_bytes()).unwrap();
+
+        let benchmark = board.benchmark();
+        assert_eq!(benchmark.openings(), 3);
+        assert_eq!(benchmark.isolated_numbers(), 0);
+        assert_eq!(benchmark.islands(), 0);
+        assert_eq!(benchmark.bbbv(), 3);
+    }
+}
@@ -0,0 +1,82 @@
+//! Undo and redo of plays.
+//!
+//! Each play stores the cells it changed, with their state before
+//!  and after the play, and the board counters before and after.
+//! This way a dig that propagates to many cells is undone at once.
+//!
+
+/// State of a cell that can be changed by a play
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub(super) struct CellState {
+    pub(super) dug: bool,
+    pub(super) marked: bool,
+}
+
+/// Values of the board that can be changed by a play
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub(super) struct Counters {
+    pub(super) cells_to_dig: u32,
+    pub(super) marked_count: u32,
+    pub(super) deadly_mine: Option<(u16, u16)>,
+}
+
+/// A cell changed by a play
+#[derive(Clone)]
+pub(super) struct Change {
+    pub(super) cell: (u16, u16),
+    pub(super) before: CellState,
+    pub(super) after: CellState,
+}
+
+/// Everything changed by a play
+#[derive(Clone)]
+pub(super) struct Step {
+    /// Changed cells, on the order they were changed
+    pub(super) changes: Vec<Change>,
+    pub(super) before: Counters,
+    pub(super) after: Counters,
+}
+
+impl Step {
+    /// If the play didn't change anything
+    pub(super) fn is_empty(&self) -> bool {
+        return self.changes.is_empty() && self.before == self.after;
+    }
+}
+
+/// Plays that can be undone and redone
+#[derive(Clone, Default)]
+pub(super) struct History {
+    /// Plays done, the last one is the next to undo
+    done: Vec<Step>,
+    /// Plays undone, the last one is the next to redo
+    undone: Vec<Step>,
+}
+
+impl History {
+    /// Stores a new play. The undone plays can't be redone anymore.
+    pub(super) fn push(&mut self, step: Step) {
+        self.undone.clear();
+        self.done.push(step);
+    }
+
+    /// Next play to undo, which can then be redone
+    pub(super) fn undo(&mut self) -> Option<&Step> {
+        let step = self.done.pop()?;
+        self.undone.push(step);
+        return self.undone.last();
+    }
+
+    /// Next play to redo, which can then be undone again
+    pub(super) fn redo(&mut self) -> Option<&Step> {
+        let step = self.undone.pop()?;
+        self.done.push(step);
+        return self.done.last();
+    }
+
+    /// Forget all plays
+    pub(super) fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+}
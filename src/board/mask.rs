@@ -0,0 +1,350 @@
+//! Masks with the cells of a board that can't be played.
+//!
+//! A [Mask] blocks some cells of the board, to make holes, walls or
+//!  boards with other shapes. The blocked cells never have mines,
+//!  aren't neighbours of any cell and can't be dug nor marked.
+//!  On boards with layers every layer has the same mask.
+//!
+//! The built-in masks are:
+//!  - `heart`: a heart of 11 rows and 13 columns;
+//!  - `ring`: a ring of 11 rows and 11 columns, with a hole in the middle.
+//!
+//! Others are loaded from a file with a drawing of the board, where
+//!  `x` are the cells that can be played and `.` or spaces the blocked
+//!  ones. The board has a row for each line and as many columns as the
+//!  longest line, the missing cells of shorter lines are blocked.
+//!  Lines starting with `#` are comments:
+//!
+//! ```text
+//! # a board with a wall in the middle
+//! xxxxxxx
+//! xxx.xxx
+//! xxx.xxx
+//! xxx.xxx
+//! xxxxxxx
+//! ```
+//!
+
+use std::error::Error;
+use std::fmt::Formatter;
+use std::path::Path;
+use std::str::FromStr;
+use std::{fmt, fs, io};
+
+use itertools::Itertools;
+
+/// Names of the built-in masks
+pub const PRESETS: [&str; 2] = ["heart", "ring"];
+
+/// Drawing of the `heart` mask
+const HEART: &str = "\
+..xxx...xxx..
+.xxxxx.xxxxx.
+xxxxxxxxxxxxx
+xxxxxxxxxxxxx
+xxxxxxxxxxxxx
+.xxxxxxxxxxx.
+..xxxxxxxxx..
+...xxxxxxx...
+....xxxxx....
+.....xxx.....
+......x......
+";
+
+/// Drawing of the `ring` mask
+const RING: &str = "\
+...xxxxx...
+.xxxxxxxxx.
+.xxxxxxxxx.
+xxxx...xxxx
+xxx.....xxx
+xxx.....xxx
+xxx.....xxx
+xxxx...xxxx
+.xxxxxxxxx.
+.xxxxxxxxx.
+...xxxxx...
+";
+
+/// Cells of a board that can't be played.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Mask {
+    /// If each cell is blocked, row by row,
+    ///  all the rows have the same length
+    blocked: Vec<Vec<bool>>,
+}
+
+impl Mask {
+    /// Constructor
+    ///
+    /// The shorter rows are filled with blocked cells.
+    ///  Fails if there are no cells that can be played,
+    ///  or too many rows or columns.
+    pub fn new(mut blocked: Vec<Vec<bool>>) -> Result<Mask, MaskError> {
+        let columns = blocked.iter().map(Vec::len).max().unwrap_or(0);
+        if blocked.len() > u16::MAX as usize || columns > u16::MAX as usize {
+            return Err(MaskError::TooBig);
+        }
+        for row in blocked.iter_mut() {
+            row.resize(columns, true);
+        }
+        let mask = Mask { blocked };
+        if mask.playable() == 0 {
+            return Err(MaskError::Empty);
+        }
+        return Ok(mask);
+    }
+
+    /// The built-in mask with the given name, see [PRESETS].
+    pub fn preset(name: &str) -> Option<Mask> {
+        return match name {
+            "heart" => Mask::parse(HEART).ok(),
+            "ring" => Mask::parse(RING).ok(),
+            _ => None,
+        };
+    }
+
+    /// Reads a drawing of a mask, see the [module](self) documentation.
+    pub fn parse(content: &str) -> Result<Mask, MaskError> {
+        let lines = content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim_start().starts_with('#'))
+            .skip_while(|(_, line)| line.trim().is_empty())
+            .collect::<Vec<(usize, &str)>>();
+        // the empty lines at the end aren't rows either
+        let count = lines
+            .iter()
+            .rposition(|(_, line)| !line.trim().is_empty())
+            .map_or(0, |last| last + 1);
+
+        let mut blocked = Vec::with_capacity(count);
+        for &(index, line) in lines[..count].iter() {
+            let row = line
+                .trim_end()
+                .chars()
+                .map(|c| match c {
+                    'x' => Ok(false),
+                    '.' | ' ' => Ok(true),
+                    found => Err(MaskError::InvalidCell {
+                        line: index + 1,
+                        found,
+                    }),
+                })
+                .collect::<Result<Vec<bool>, MaskError>>()?;
+            blocked.push(row);
+        }
+        return Mask::new(blocked);
+    }
+
+    /// Loads a mask file, see the [module](self) documentation.
+    pub fn load(path: &Path) -> Result<Mask, MaskError> {
+        return Mask::parse(&fs::read_to_string(path)?);
+    }
+
+    /// The built-in mask with the given name, or else
+    ///  the mask file on that path.
+    pub fn find(name: &str) -> Result<Mask, MaskError> {
+        return match Mask::preset(name) {
+            Some(mask) => Ok(mask),
+            None => Mask::load(Path::new(name)),
+        };
+    }
+
+    /// Number of rows of the mask
+    pub fn rows(&self) -> u16 {
+        return self.blocked.len() as u16;
+    }
+
+    /// Number of columns of the mask
+    pub fn columns(&self) -> u16 {
+        return self.blocked.first().map_or(0, Vec::len) as u16;
+    }
+
+    /// If the cell can't be played, the cells outside of the mask aren't blocked.
+    pub fn is_blocked(&self, row: u16, column: u16) -> bool {
+        return self
+            .blocked
+            .get(row as usize)
+            .and_then(|cells| cells.get(column as usize))
+            .copied()
+            .unwrap_or(false);
+    }
+
+    /// Number of cells that can be played.
+    pub fn playable(&self) -> u32 {
+        return self
+            .blocked
+            .iter()
+            .flatten()
+            .filter(|&&blocked| !blocked)
+            .count() as u32;
+    }
+
+    /// Name of the built-in mask equal to this one, if any.
+    pub fn name(&self) -> Option<&'static str> {
+        return PRESETS
+            .iter()
+            .copied()
+            .find(|name| Mask::preset(name).as_ref() == Some(self));
+    }
+}
+
+impl fmt::Display for Mask {
+    /// The name of the built-in mask, or else the rows
+    ///  of the drawing separated by `/`, ex: `xxx/x.x/xxx`.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{}", name),
+            None => write!(
+                f,
+                "{}",
+                self.blocked
+                    .iter()
+                    .map(|row| {
+                        row.iter()
+                            .map(|&blocked| if blocked { '.' } else { 'x' })
+                            .collect::<String>()
+                    })
+                    .join("/")
+            ),
+        }
+    }
+}
+
+impl FromStr for Mask {
+    type Err = MaskError;
+
+    /// Reads the name of a built-in mask or the rows
+    ///  written when it is displayed.
+    fn from_str(value: &str) -> Result<Mask, MaskError> {
+        if let Some(mask) = Mask::preset(value) {
+            return Ok(mask);
+        }
+        let blocked = value
+            .split('/')
+            .map(|row| {
+                row.chars()
+                    .map(|c| match c {
+                        'x' => Ok(false),
+                        '.' => Ok(true),
+                        _ => Err(MaskError::InvalidRows(value.to_string())),
+                    })
+                    .collect::<Result<Vec<bool>, MaskError>>()
+            })
+            .collect::<Result<Vec<Vec<bool>>, MaskError>>()?;
+        return Mask::new(blocked);
+    }
+}
+
+/// Returned when a mask can't be used
+#[derive(Debug)]
+pub enum MaskError {
+    /// The file can't be read
+    Io(io::Error),
+    /// A character of the drawing isn't `x`, `.` nor a space
+    InvalidCell {
+        /// number of the line
+        line: usize,
+        /// character found
+        found: char,
+    },
+    /// There are no cells that can be played
+    Empty,
+    /// The drawing has more rows or columns than a board can have
+    TooBig,
+    /// The rows aren't made of `x` and `.` separated by `/`
+    InvalidRows(String),
+}
+
+impl Error for MaskError {}
+
+impl fmt::Display for MaskError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            MaskError::Io(e) => write!(f, "Can't read the mask: {}", e),
+            MaskError::InvalidCell { line, found } => write!(
+                f,
+                "Invalid cell {:?} on line {} of the mask, \
+                 expected x for a cell, . or a space for a blocked one",
+                found, line
+            ),
+            MaskError::Empty => write!(f, "The mask has no cells to play"),
+            MaskError::TooBig => write!(f, "The mask is too big for a board"),
+            MaskError::InvalidRows(value) => write!(f, "Invalid mask rows {:?}", value),
+        }
+    }
+}
+
+impl From<io::Error> for MaskError {
+    fn from(e: io::Error) -> MaskError {
+        return MaskError::Io(e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::arguments::{InvalidParameters, Parameters};
+
+    #[test]
+    fn drawings_are_read_row_by_row() {
+        let mask = Mask::parse("# a wall\n\nxxx\nx.x\nx x\n").unwrap();
+        assert_eq!((mask.rows(), mask.columns()), (3, 3));
+        assert!(mask.is_blocked(1, 1));
+        assert!(mask.is_blocked(2, 1));
+        assert_eq!(mask.playable(), 7);
+        assert_eq!(mask.to_string(), "xxx/x.x/x.x");
+
+        assert!(matches!(
+            Mask::parse("xxx\nxox"),
+            Err(MaskError::InvalidCell {
+                line: 2,
+                found: 'o'
+            })
+        ));
+        assert!(matches!(Mask::parse("...\n. ."), Err(MaskError::Empty)));
+        assert!(matches!(
+            "xxx/x-x".parse::<Mask>(),
+            Err(MaskError::InvalidRows(_))
+        ));
+    }
+
+    #[test]
+    fn shorter_rows_are_blocked_at_the_end() {
+        let mask = Mask::parse("xxxx\nx\nxx").unwrap();
+        assert_eq!((mask.rows(), mask.columns()), (3, 4));
+        assert_eq!(mask.to_string(), "xxxx/x.../xx..");
+        assert_eq!("xxxx/x/xx".parse::<Mask>().unwrap(), mask);
+    }
+
+    #[test]
+    fn presets_are_read_by_name() {
+        let heart = Mask::preset("heart").unwrap();
+        assert_eq!((heart.rows(), heart.columns()), (11, 13));
+        assert_eq!(heart.to_string(), "heart");
+        assert_eq!("heart".parse::<Mask>().unwrap(), heart);
+        assert_eq!(Mask::preset("ring").unwrap().name(), Some("ring"));
+        assert_eq!(Mask::preset("square"), None);
+    }
+
+    #[test]
+    fn masks_have_the_size_of_the_board() {
+        let mask = "xxx/x.x/xxx".parse::<Mask>().unwrap();
+        assert!(Parameters::new(3, 3, 1)
+            .unwrap()
+            .with_mask(mask.clone())
+            .is_ok());
+        assert!(matches!(
+            Parameters::new(3, 4, 1).unwrap().with_mask(mask.clone()),
+            Err(InvalidParameters::MaskSize {
+                rows: 3,
+                columns: 3
+            })
+        ));
+        assert!(matches!(
+            Parameters::new(4, 3, 1).unwrap().with_mask(mask),
+            Err(InvalidParameters::MaskSize { .. })
+        ));
+    }
+}
@@ -0,0 +1,372 @@
+//! Custom neighbourhoods of the cells of square boards.
+//!
+//! By default the number of a cell counts the mines on the 8 cells
+//!  around it, a [Neighbourhood] counts the mines on other cells instead,
+//!  ex: the ones a chess knight reaches. The digs of empty cells, chords
+//!  and the [solver](crate::solver) use the same cells.
+//!
+//! The built-in neighbourhoods are:
+//!  - `king`: the 8 cells around, as on the classic game;
+//!  - `knight`: the 8 cells a chess knight reaches;
+//!  - `cross`: the 4 cells on the same row or column;
+//!  - `radius-2`: the 24 cells of the 5x5 square around.
+//!
+//! Others are loaded from a file with a drawing of the neighbourhood,
+//!  where `o` is the cell, `x` are the cells counted and `.` the ones
+//!  that aren't, up to [MAX_REACH] cells away. Lines starting with `#`
+//!  are comments:
+//!
+//! ```text
+//! # cells on the same row or column, up to 2 cells away
+//! ..x..
+//! ..x..
+//! xxoxx
+//! ..x..
+//! ..x..
+//! ```
+//!
+
+use std::error::Error;
+use std::fmt::Formatter;
+use std::path::Path;
+use std::str::FromStr;
+use std::{fmt, fs, io};
+
+use itertools::Itertools;
+
+/// Names of the built-in neighbourhoods
+pub const PRESETS: [&str; 4] = ["king", "knight", "cross", "radius-2"];
+
+/// Farthest row or column difference of the cells counted
+pub const MAX_REACH: i32 = 100;
+
+/// Cells whose mines are counted on the number of a cell,
+///  as row and column differences from it.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Neighbourhood {
+    /// Differences, sorted by row and then column
+    offsets: Vec<(i32, i32)>,
+}
+
+impl Neighbourhood {
+    /// Constructor
+    ///
+    /// The offsets are sorted. Fails if there are none, if one is the
+    ///  cell itself (`0,0`), is repeated or is more than [MAX_REACH]
+    ///  rows or columns away.
+    pub fn new(offsets: Vec<(i32, i32)>) -> Result<Neighbourhood, NeighbourhoodError> {
+        let offsets = offsets.into_iter().sorted().collect::<Vec<(i32, i32)>>();
+        if offsets.is_empty() {
+            return Err(NeighbourhoodError::Empty);
+        }
+        if offsets.contains(&(0, 0)) {
+            return Err(NeighbourhoodError::CountsItself);
+        }
+        if let Some(&(row, column)) = offsets
+            .iter()
+            .find(|(r, c)| r.abs() > MAX_REACH || c.abs() > MAX_REACH)
+        {
+            return Err(NeighbourhoodError::TooFar(row, column));
+        }
+        if let Some((&(row, column), _)) = offsets.iter().tuple_windows().find(|(a, b)| a == b) {
+            return Err(NeighbourhoodError::RepeatedOffset(row, column));
+        }
+        return Ok(Neighbourhood { offsets });
+    }
+
+    /// The built-in neighbourhood with the given name, see [PRESETS].
+    pub fn preset(name: &str) -> Option<Neighbourhood> {
+        let square = |radius: i32| {
+            (-radius..=radius)
+                .cartesian_product(-radius..=radius)
+                .filter(|&offset| offset != (0, 0))
+                .collect::<Vec<(i32, i32)>>()
+        };
+        let offsets = match name {
+            "king" => square(1),
+            "knight" => square(2)
+                .into_iter()
+                .filter(|(r, c)| r.abs() + c.abs() == 3)
+                .collect(),
+            "cross" => vec![(-1, 0), (0, -1), (0, 1), (1, 0)],
+            "radius-2" => square(2),
+            _ => return None,
+        };
+        return Neighbourhood::new(offsets).ok();
+    }
+
+    /// Reads a drawing of a neighbourhood, see the [module](self) documentation.
+    pub fn parse(content: &str) -> Result<Neighbourhood, NeighbourhoodError> {
+        let mut centre = None;
+        let mut cells = Vec::new();
+        let lines = content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim_start().starts_with('#'))
+            .skip_while(|(_, line)| line.trim().is_empty());
+        for (row, (index, line)) in lines.enumerate() {
+            for (column, c) in line.trim_end().chars().enumerate() {
+                let cell = (row as i32, column as i32);
+                match c {
+                    'x' => cells.push(cell),
+                    'o' if centre.is_none() => centre = Some(cell),
+                    '.' => {}
+                    found => {
+                        return Err(NeighbourhoodError::InvalidCell {
+                            line: index + 1,
+                            found,
+                        })
+                    }
+                }
+            }
+        }
+
+        let (row, column) = centre.ok_or(NeighbourhoodError::NoCentre)?;
+        return Neighbourhood::new(
+            cells
+                .into_iter()
+                .map(|(r, c)| (r - row, c - column))
+                .collect(),
+        );
+    }
+
+    /// Loads a neighbourhood file, see the [module](self) documentation.
+    pub fn load(path: &Path) -> Result<Neighbourhood, NeighbourhoodError> {
+        return Neighbourhood::parse(&fs::read_to_string(path)?);
+    }
+
+    /// The built-in neighbourhood with the given name, or else
+    ///  the neighbourhood file on that path.
+    pub fn find(name: &str) -> Result<Neighbourhood, NeighbourhoodError> {
+        return match Neighbourhood::preset(name) {
+            Some(neighbourhood) => Ok(neighbourhood),
+            None => Neighbourhood::load(Path::new(name)),
+        };
+    }
+
+    /// Offsets getter
+    pub fn offsets(&self) -> &[(i32, i32)] {
+        return &self.offsets;
+    }
+
+    /// The farthest row or column difference of the cells counted.
+    pub fn reach(&self) -> i32 {
+        return self
+            .offsets
+            .iter()
+            .map(|(r, c)| r.abs().max(c.abs()))
+            .max()
+            .unwrap_or(0);
+    }
+
+    /// Name of the built-in neighbourhood equal to this one, if any.
+    pub fn name(&self) -> Option<&'static str> {
+        return PRESETS
+            .iter()
+            .copied()
+            .find(|name| Neighbourhood::preset(name).as_ref() == Some(self));
+    }
+}
+
+impl fmt::Display for Neighbourhood {
+    /// The name of the built-in neighbourhood, or else the offsets,
+    ///  ex: `-1,0 0,-1 0,1 1,0`.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{}", name),
+            None => write!(
+                f,
+                "{}",
+                self.offsets
+                    .iter()
+                    .map(|(r, c)| format!("{},{}", r, c))
+                    .join(" ")
+            ),
+        }
+    }
+}
+
+impl FromStr for Neighbourhood {
+    type Err = NeighbourhoodError;
+
+    /// Reads the name of a built-in neighbourhood or the offsets
+    ///  written when it is displayed.
+    fn from_str(value: &str) -> Result<Neighbourhood, NeighbourhoodError> {
+        if let Some(neighbourhood) = Neighbourhood::preset(value) {
+            return Ok(neighbourhood);
+        }
+        let invalid = || NeighbourhoodError::InvalidOffsets(value.to_string());
+        let offsets = value
+            .split_whitespace()
+            .map(|offset| {
+                let (r, c) = offset.split_once(',').ok_or_else(invalid)?;
+                match (r.parse(), c.parse()) {
+                    (Ok(r), Ok(c)) => Ok((r, c)),
+                    _ => Err(invalid()),
+                }
+            })
+            .collect::<Result<Vec<(i32, i32)>, NeighbourhoodError>>()?;
+        return Neighbourhood::new(offsets);
+    }
+}
+
+/// Returned when a neighbourhood can't be used
+#[derive(Debug)]
+pub enum NeighbourhoodError {
+    /// The file can't be read
+    Io(io::Error),
+    /// A character of the drawing isn't `o`, `x` nor `.`,
+    ///  or it's a second `o`
+    InvalidCell {
+        /// number of the line
+        line: usize,
+        /// character found
+        found: char,
+    },
+    /// The drawing doesn't have the cell (`o`)
+    NoCentre,
+    /// There are no cells
+    Empty,
+    /// The cell itself is counted
+    CountsItself,
+    /// A cell is counted more than once
+    RepeatedOffset(i32, i32),
+    /// A cell is more than [MAX_REACH] rows or columns away
+    TooFar(i32, i32),
+    /// The offsets aren't `row,column` pairs
+    InvalidOffsets(String),
+}
+
+impl Error for NeighbourhoodError {}
+
+impl fmt::Display for NeighbourhoodError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            NeighbourhoodError::Io(e) => write!(f, "Can't read the neighbourhood: {}", e),
+            NeighbourhoodError::InvalidCell { line, found } => write!(
+                f,
+                "Invalid cell {:?} on line {} of the neighbourhood, \
+                 expected one o for the cell, x or .",
+                found, line
+            ),
+            NeighbourhoodError::NoCentre => {
+                write!(f, "The neighbourhood doesn't have the cell (o)")
+            }
+            NeighbourhoodError::Empty => write!(f, "The neighbourhood has no cells"),
+            NeighbourhoodError::CountsItself => {
+                write!(f, "The neighbourhood can't count the cell itself (0,0)")
+            }
+            NeighbourhoodError::RepeatedOffset(row, column) => {
+                write!(
+                    f,
+                    "The neighbourhood counts {},{} more than once",
+                    row, column
+                )
+            }
+            NeighbourhoodError::TooFar(row, column) => write!(
+                f,
+                "The neighbourhood cell {},{} is more than {} rows or columns away",
+                row, column, MAX_REACH
+            ),
+            NeighbourhoodError::InvalidOffsets(value) => {
+                write!(f, "Invalid neighbourhood offsets {:?}", value)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for NeighbourhoodError {
+    fn from(e: io::Error) -> NeighbourhoodError {
+        return NeighbourhoodError::Io(e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drawings_are_read_around_the_cell() {
+        let drawing = "# the cells above\n\nx.x\n.o.\n";
+        let neighbourhood = Neighbourhood::parse(drawing).unwrap();
+        assert_eq!(neighbourhood.offsets(), &[(-1, -1), (-1, 1)]);
+        assert_eq!(neighbourhood.reach(), 1);
+        assert_eq!(neighbourhood.name(), None);
+
+        assert!(matches!(
+            Neighbourhood::parse("x.x\n.o.\n..?"),
+            Err(NeighbourhoodError::InvalidCell {
+                line: 3,
+                found: '?'
+            })
+        ));
+        assert!(matches!(
+            Neighbourhood::parse("xox\n.o."),
+            Err(NeighbourhoodError::InvalidCell {
+                line: 2,
+                found: 'o'
+            })
+        ));
+        assert!(matches!(
+            Neighbourhood::parse("x.x"),
+            Err(NeighbourhoodError::NoCentre)
+        ));
+        assert!(matches!(
+            Neighbourhood::parse(".o."),
+            Err(NeighbourhoodError::Empty)
+        ));
+    }
+
+    #[test]
+    fn presets_have_their_cells() {
+        let count = |name: &str| Neighbourhood::preset(name).unwrap().offsets().len();
+        assert_eq!(count("king"), 8);
+        assert_eq!(count("knight"), 8);
+        assert_eq!(count("cross"), 4);
+        assert_eq!(count("radius-2"), 24);
+        assert_eq!(Neighbourhood::preset("radius-2").unwrap().reach(), 2);
+        assert_eq!(Neighbourhood::preset("queen"), None);
+        for name in PRESETS {
+            assert_eq!(Neighbourhood::preset(name).unwrap().name(), Some(name));
+        }
+    }
+
+    #[test]
+    fn neighbourhoods_are_read_as_displayed() {
+        let knight = Neighbourhood::preset("knight").unwrap();
+        assert_eq!(knight.to_string(), "knight");
+        assert_eq!("knight".parse::<Neighbourhood>().unwrap(), knight);
+
+        let above = Neighbourhood::new(vec![(-1, 1), (-2, 0), (-1, -1)]).unwrap();
+        assert_eq!(above.to_string(), "-2,0 -1,-1 -1,1");
+        assert_eq!(above.to_string().parse::<Neighbourhood>().unwrap(), above);
+
+        for value in ["", "1", "1,x", "1;0"] {
+            assert!(
+                value.parse::<Neighbourhood>().is_err(),
+                "{:?} was read",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_offsets_are_refused() {
+        assert!(matches!(
+            "0,0 1,0".parse::<Neighbourhood>(),
+            Err(NeighbourhoodError::CountsItself)
+        ));
+        assert!(matches!(
+            "1,0 0,1 1,0".parse::<Neighbourhood>(),
+            Err(NeighbourhoodError::RepeatedOffset(1, 0))
+        ));
+        assert!(matches!(
+            "1,0 0,101".parse::<Neighbourhood>(),
+            Err(NeighbourhoodError::TooFar(0, 101))
+        ));
+        assert!(matches!(
+            "1,0 99999999999,0".parse::<Neighbourhood>(),
+            Err(NeighbourhoodError::InvalidOffsets(_))
+        ));
+    }
+}
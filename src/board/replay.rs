@@ -0,0 +1,904 @@
+//! Recording of the plays of a game so it can be watched again.
+//!
+//! A replay is a text file, starting with a line with its format
+//!  version, then a line for each game variable, the mines layout
+//!  (`*` for a mine, `.` for a safe cell), the plays, one per line,
+//!  with the milliseconds since the first play and at last how the
+//!  game ended, so it can be [verified](crate::board::verify):
+//!
+//! ```text
+//! mineswipper replay 1
+//! rows 2
+//! columns 3
+//! layers 1
+//! mines 1
+//! topology square
+//! neighbourhood none
+//! mask none
+//! wrap false
+//! seed 1234
+//! no_guess none
+//! flag_limit none
+//! practice false
+//! layout
+//! ...
+//! *..
+//! plays
+//! 0 d 0 2
+//! 1500 m 1 0
+//! 2100 undo
+//! end
+//! result continue
+//! time_ms 2500
+//! moves 3
+//! ```
+//!
+//! The plays use the same syntax as the game input and
+//!  the first one is always the dig that built the board.
+//! On boards with layers the layout has the rows of all the layers,
+//!  one layer after another, and the plays are written with the
+//!  rows numbered the same way, ex: `d 5 2` is the row 1 of the
+//!  layer 1 of a board with 4 rows, as `d 1 2 1` on the input.
+//!  Plays written with the layer, as on the input, are also read.
+//! The mask is written as on [saves](crate::board::save), and
+//!  its blocked cells are `.` on the layout, since they have no mines.
+//!
+//! The time is the one of the play that ended the game or,
+//!  if the game didn't end, when the recording stopped.
+//!
+//! As [saves](crate::board::save), the replay is written to
+//!  a temporary file next to it that then replaces it.
+//!
+
+use std::error::Error;
+use std::fmt::Formatter;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::time::Duration;
+use std::{fmt, io};
+
+use crate::board::save::write_atomically;
+use crate::board::{Board, CellValue, GameResult};
+use crate::input::arguments::{InvalidParameters, Parameters};
+use crate::input::plays::{parse_following_play, Command, Play, PlayMode};
+
+/// First line of every replay file
+const MAGIC: &str = "mineswipper replay";
+
+/// Current version of the replay format
+const VERSION: u32 = 1;
+
+/// Something the player did to the board
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Action {
+    /// A play over a cell
+    Play(Play),
+    /// Revert the last play
+    Undo,
+    /// Apply again the last undone play
+    Redo,
+}
+
+/// Same format as the input, ex: `m 3 3` or `undo`
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Action::Play(play) => play.fmt(f),
+            Action::Undo => write!(f, "undo"),
+            Action::Redo => write!(f, "redo"),
+        }
+    }
+}
+
+/// An action and when it happened
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Event {
+    /// Time since the first play
+    time: Duration,
+    /// What was done
+    action: Action,
+}
+
+/// Just getters
+impl Event {
+    /// Time getter
+    pub fn time(&self) -> Duration {
+        return self.time;
+    }
+
+    /// Action getter
+    pub fn action(&self) -> &Action {
+        return &self.action;
+    }
+}
+
+/// Returned when a replay can't be read
+#[derive(Debug)]
+pub enum ReplayError {
+    /// The file can't be read
+    Io(io::Error),
+    /// The file doesn't start with the replay header
+    NotAReplay,
+    /// The file was written by a newer version of the game
+    UnsupportedVersion(String),
+    /// A game variable is missing
+    MissingField(&'static str),
+    /// A game variable can't be parsed
+    InvalidField {
+        /// name of the variable
+        field: &'static str,
+        /// value on the file
+        value: String,
+    },
+    /// The game variables are invalid
+    Parameters(InvalidParameters),
+    /// A line of the layout is not valid
+    InvalidLayout {
+        /// row of the layout
+        row: usize,
+        /// line found
+        line: String,
+    },
+    /// The layout doesn't have the declared number of rows
+    WrongRowCount {
+        /// declared rows
+        expected: u16,
+        /// rows found
+        found: usize,
+    },
+    /// The declared number of mines doesn't match the layout
+    MinesMismatch {
+        /// declared mines
+        declared: u32,
+        /// mines on the layout
+        found: u32,
+    },
+    /// A play can't be parsed or is outside of the board
+    InvalidEvent {
+        /// number of the play, starting on 0
+        index: usize,
+        /// line found
+        line: String,
+    },
+    /// The first play is not a dig on a safe cell that can be played
+    InvalidStart,
+}
+
+impl Error for ReplayError {}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Io(e) => write!(f, "Can't read the replay: {}", e),
+            ReplayError::NotAReplay => write!(f, "Not a replay file"),
+            ReplayError::UnsupportedVersion(v) => write!(f, "Unsupported replay version {}", v),
+            ReplayError::MissingField(field) => write!(f, "Missing {} on the replay", field),
+            ReplayError::InvalidField { field, value } => {
+                write!(f, "Invalid {} on the replay: {:?}", field, value)
+            }
+            ReplayError::Parameters(e) => e.fmt(f),
+            ReplayError::InvalidLayout { row, line } => {
+                write!(f, "Invalid row {} of the layout: {:?}", row, line)
+            }
+            ReplayError::WrongRowCount { expected, found } => {
+                write!(
+                    f,
+                    "Expected {} rows on the layout, found {}",
+                    expected, found
+                )
+            }
+            ReplayError::MinesMismatch { declared, found } => write!(
+                f,
+                "The replay declares {} mines but the layout has {}",
+                declared, found
+            ),
+            ReplayError::InvalidEvent { index, line } => {
+                write!(f, "Invalid play {}: {:?}", index, line)
+            }
+            ReplayError::InvalidStart => {
+                write!(f, "The first play must be a dig on a cell without mine")
+            }
+        }
+    }
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(e: io::Error) -> ReplayError {
+        return ReplayError::Io(e);
+    }
+}
+
+impl From<InvalidParameters> for ReplayError {
+    fn from(e: InvalidParameters) -> ReplayError {
+        return ReplayError::Parameters(e);
+    }
+}
+
+/// Auxiliary function to write an optional value
+fn optional<T: fmt::Display>(value: Option<T>) -> String {
+    return match value {
+        Some(value) => value.to_string(),
+        None => "none".to_string(),
+    };
+}
+
+/// Auxiliary function to read a game variable from the next line
+fn field<T: std::str::FromStr, I: Iterator<Item = io::Result<String>>>(
+    lines: &mut I,
+    field: &'static str,
+) -> Result<Option<T>, ReplayError> {
+    let line = lines.next().ok_or(ReplayError::MissingField(field))??;
+    let value = match line.split_once(' ') {
+        Some((name, value)) if name == field => value,
+        _ => return Err(ReplayError::MissingField(field)),
+    };
+    if value == "none" {
+        return Ok(None);
+    }
+    return match value.parse::<T>() {
+        Ok(value) => Ok(Some(value)),
+        Err(_) => Err(ReplayError::InvalidField {
+            field,
+            value: value.to_string(),
+        }),
+    };
+}
+
+/// Auxiliary function to read a game variable that can't be `none`
+fn required<T: std::str::FromStr, I: Iterator<Item = io::Result<String>>>(
+    lines: &mut I,
+    name: &'static str,
+) -> Result<T, ReplayError> {
+    return field(lines, name)?.ok_or(ReplayError::InvalidField {
+        field: name,
+        value: "none".to_string(),
+    });
+}
+
+/// How the recorded game ended, according to the recording.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Claims {
+    /// Result of the game
+    result: GameResult,
+    /// Time played
+    time: Duration,
+    /// Number of plays, including undo and redo
+    moves: usize,
+}
+
+/// Just getters
+impl Claims {
+    /// Result getter
+    pub fn result(&self) -> GameResult {
+        return self.result;
+    }
+
+    /// Time getter
+    pub fn time(&self) -> Duration {
+        return self.time;
+    }
+
+    /// Moves getter
+    pub fn moves(&self) -> usize {
+        return self.moves;
+    }
+}
+
+/// Auxiliary function to write a result on the replay
+fn result_name(result: GameResult) -> &'static str {
+    return match result {
+        GameResult::Won => "won",
+        GameResult::Lost => "lost",
+        GameResult::Continue => "continue",
+    };
+}
+
+/// All the plays of a game and what is needed to apply them again.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    /// Game variables
+    params: Parameters,
+    /// Mines of the board, sorted
+    layout: Vec<(u16, u16)>,
+    /// Plays, the first one is the dig that built the board
+    events: Vec<Event>,
+    /// How the game ended, `None` while recording
+    claims: Option<Claims>,
+}
+
+impl Replay {
+    /// Starts recording a game.
+    ///
+    /// Must be called right after the board is [built](Board::build),
+    ///  with the cell of the first play.
+    pub fn record(board: &Board, start: (u16, u16)) -> Replay {
+        let mut layout = Vec::with_capacity(board.mines_count as usize);
+        for (i, row) in board.board.iter().enumerate() {
+            for (j, cell) in row.iter().enumerate() {
+                if cell.value == CellValue::Mine {
+                    layout.push((i as u16, j as u16));
+                }
+            }
+        }
+
+        // the board was valid, so its variables are too
+        let params =
+            Parameters::layered(board.rows, board.columns, board.layers, board.mines_count)
+                .and_then(|params| params.with_topology(board.topology))
+                .expect("Invalid board variables");
+        let params = match &board.neighbourhood {
+            Some(neighbourhood) => params
+                .with_neighbourhood(neighbourhood.clone())
+                .expect("Invalid board variables"),
+            None => params,
+        };
+        let params = match &board.mask {
+            Some(mask) => params
+                .with_mask(mask.clone())
+                .expect("Invalid board variables"),
+            None => params,
+        };
+        let params = match board.wrap {
+            true => params.with_wrap().expect("Invalid board variables"),
+            false => params,
+        };
+        let params = match board.seed {
+            Some(seed) => params.with_seed(seed),
+            None => params,
+        };
+        let params = match board.no_guess {
+            Some(attempts) => params.with_no_guess(attempts),
+            None => params,
+        };
+        let params = match board.flag_limit {
+            Some(limit) => params.with_flag_limit(limit),
+            None => params,
+        };
+        let params = match board.practice {
+            true => params.with_practice(),
+            false => params,
+        };
+
+        return Replay {
+            params,
+            layout,
+            events: vec![Event {
+                time: Duration::ZERO,
+                action: Action::Play(Play::new(PlayMode::Dig, start)),
+            }],
+            claims: None,
+        };
+    }
+
+    /// Stops recording, storing how the game ended.
+    pub fn finish(&mut self, board: &Board) {
+        let result = board.result();
+        let time = match (result, self.events.last()) {
+            (GameResult::Continue, _) | (_, None) => board.elapsed(),
+            (_, Some(last)) => last.time,
+        };
+        self.claims = Some(Claims {
+            result,
+            time,
+            moves: self.events.len(),
+        });
+    }
+
+    /// Adds an action done to the board, timed by the board clock.
+    pub fn push(&mut self, board: &Board, action: Action) {
+        self.events.push(Event {
+            time: board.elapsed(),
+            action,
+        });
+    }
+
+    /// Game variables getter
+    pub fn params(&self) -> &Parameters {
+        return &self.params;
+    }
+
+    /// Mines getter
+    pub fn layout(&self) -> &[(u16, u16)] {
+        return &self.layout;
+    }
+
+    /// Plays getter
+    pub fn events(&self) -> &[Event] {
+        return &self.events;
+    }
+
+    /// How the game ended, `None` if the recording wasn't
+    ///  [finished](Replay::finish).
+    pub fn claims(&self) -> Option<&Claims> {
+        return self.claims.as_ref();
+    }
+
+    /// Board with the mines of the replay, before the first play.
+    pub fn initial_board(&self) -> Board {
+        let mut board = Board::new(&self.params);
+        board.seed = self.params.seed();
+        for &(row, column) in self.layout.iter() {
+            board.board[row as usize][column as usize].value = CellValue::Mine;
+        }
+        board.cells_to_dig -= self.layout.len() as u32;
+        board.place_numbers(self.layout.iter().copied().collect());
+        return board;
+    }
+
+    /// Applies a play of the replay to the board.
+    ///
+    /// The first play builds the board, so it can't be undone.
+    pub fn apply(board: &mut Board, event: &Event, first: bool) -> GameResult {
+        return match event.action {
+            Action::Play(play) if first => {
+                board.resume();
+                board.clicks.count(PlayMode::Dig, true);
+                board.propagate_dig(play.cell());
+                board.update_timer();
+                board.result()
+            }
+            Action::Play(play) => match play.mode() {
+                PlayMode::Dig => board.dig(play.cell()),
+                PlayMode::Chord => board.chord(play.cell()),
+                PlayMode::Mark => {
+                    board.mark(play.cell());
+                    board.result()
+                }
+                PlayMode::Unmark => {
+                    board.unmark(play.cell());
+                    board.result()
+                }
+            },
+            Action::Undo => {
+                board.undo();
+                board.result()
+            }
+            Action::Redo => {
+                board.redo();
+                board.result()
+            }
+        };
+    }
+
+    /// Writes the replay to a file, see the [replay](crate::board::replay) format.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        return write_atomically(path, |writer| self.write(writer));
+    }
+
+    /// Writes the replay in the [replay](crate::board::replay) format.
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let params = &self.params;
+        writeln!(writer, "{} {}", MAGIC, VERSION)?;
+        writeln!(writer, "rows {}", params.rows())?;
+        writeln!(writer, "columns {}", params.columns())?;
+        writeln!(writer, "layers {}", params.layers())?;
+        writeln!(writer, "mines {}", params.mines())?;
+        writeln!(writer, "topology {}", params.topology())?;
+        writeln!(writer, "neighbourhood {}", optional(params.neighbourhood()))?;
+        writeln!(writer, "mask {}", optional(params.mask()))?;
+        writeln!(writer, "wrap {}", params.wrap())?;
+        writeln!(writer, "seed {}", optional(params.seed()))?;
+        writeln!(writer, "no_guess {}", optional(params.no_guess()))?;
+        writeln!(writer, "flag_limit {}", optional(params.flag_limit()))?;
+        writeln!(writer, "practice {}", params.practice())?;
+
+        writeln!(writer, "layout")?;
+        let mut mines = self.layout.iter().peekable();
+        for row in 0..params.rows() * params.layers() {
+            let mut line = String::with_capacity(params.columns() as usize);
+            for column in 0..params.columns() {
+                if mines.peek() == Some(&&(row, column)) {
+                    mines.next();
+                    line.push('*');
+                } else {
+                    line.push('.');
+                }
+            }
+            writeln!(writer, "{}", line)?;
+        }
+
+        writeln!(writer, "plays")?;
+        for event in self.events.iter() {
+            writeln!(writer, "{} {}", event.time.as_millis(), event.action)?;
+        }
+
+        if let Some(claims) = &self.claims {
+            writeln!(writer, "end")?;
+            writeln!(writer, "result {}", result_name(claims.result))?;
+            writeln!(writer, "time_ms {}", claims.time.as_millis())?;
+            writeln!(writer, "moves {}", claims.moves)?;
+        }
+
+        return Ok(());
+    }
+
+    /// Reads a replay from a file, see the [replay](crate::board::replay) format.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Replay, ReplayError> {
+        return Replay::read(BufReader::new(File::open(path)?));
+    }
+
+    /// Reads a replay in the [replay](crate::board::replay) format.
+    ///
+    /// Only checks that the replay can be played, with all the plays on
+    ///  cells of the board that aren't blocked by its mask, see
+    ///  [verify](crate::board::verify) to check if it was tampered.
+    pub fn read<R: BufRead>(reader: R) -> Result<Replay, ReplayError> {
+        let mut lines = reader.lines();
+
+        let header = lines.next().ok_or(ReplayError::NotAReplay)??;
+        let version = match header.strip_prefix(MAGIC) {
+            Some(version) => version.trim(),
+            None => return Err(ReplayError::NotAReplay),
+        };
+        if version.parse::<u32>() != Ok(VERSION) {
+            return Err(ReplayError::UnsupportedVersion(version.to_string()));
+        }
+
+        let rows = required(&mut lines, "rows")?;
+        let columns = required(&mut lines, "columns")?;
+        let layers = required(&mut lines, "layers")?;
+        let mines = required(&mut lines, "mines")?;
+        let params = Parameters::layered(rows, columns, layers, mines)?
+            .with_topology(required(&mut lines, "topology")?)?;
+        let params = match field(&mut lines, "neighbourhood")? {
+            Some(neighbourhood) => params.with_neighbourhood(neighbourhood)?,
+            None => params,
+        };
+        let params = match field(&mut lines, "mask")? {
+            Some(mask) => params.with_mask(mask)?,
+            None => params,
+        };
+        let params = match required(&mut lines, "wrap")? {
+            true => params.with_wrap()?,
+            false => params,
+        };
+        let params = match field(&mut lines, "seed")? {
+            Some(seed) => params.with_seed(seed),
+            None => params,
+        };
+        let params = match field(&mut lines, "no_guess")? {
+            Some(attempts) => params.with_no_guess(attempts),
+            None => params,
+        };
+        let params = match field(&mut lines, "flag_limit")? {
+            Some(limit) => params.with_flag_limit(limit),
+            None => params,
+        };
+        let params = match required(&mut lines, "practice")? {
+            true => params.with_practice(),
+            false => params,
+        };
+
+        match lines.next() {
+            Some(Ok(line)) if line == "layout" => {}
+            _ => return Err(ReplayError::MissingField("layout")),
+        }
+        // the rows of all the layers, checked by the parameters
+        let rows = rows * layers;
+        let blocked = |(row, column): (u16, u16)| {
+            params
+                .mask()
+                .is_some_and(|mask| mask.is_blocked(row % params.rows(), column))
+        };
+        let mut layout = Vec::with_capacity(mines as usize);
+        let mut found_rows = 0;
+        for line in lines.by_ref() {
+            let line = line?;
+            if line == "plays" {
+                break;
+            }
+            let valid = line.len() == columns as usize
+                && line.chars().enumerate().all(|(column, c)| match c {
+                    '.' => true,
+                    '*' => !blocked((found_rows as u16, column as u16)),
+                    _ => false,
+                });
+            if !valid || found_rows >= rows as usize {
+                return Err(ReplayError::InvalidLayout {
+                    row: found_rows,
+                    line,
+                });
+            }
+            for (column, c) in line.chars().enumerate() {
+                if c == '*' {
+                    layout.push((found_rows as u16, column as u16));
+                }
+            }
+            found_rows += 1;
+        }
+        if found_rows != rows as usize {
+            return Err(ReplayError::WrongRowCount {
+                expected: rows,
+                found: found_rows,
+            });
+        }
+        if layout.len() as u32 != mines {
+            return Err(ReplayError::MinesMismatch {
+                declared: mines,
+                found: layout.len() as u32,
+            });
+        }
+
+        let mut events = Vec::new();
+        let mut ended = false;
+        for (index, line) in lines.by_ref().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if line == "end" {
+                ended = true;
+                break;
+            }
+            let invalid = || ReplayError::InvalidEvent {
+                index,
+                line: line.clone(),
+            };
+
+            let (time, action) = line.split_once(' ').ok_or_else(invalid)?;
+            let time = Duration::from_millis(time.parse::<u64>().map_err(|_| invalid())?);
+            let action = match parse_following_play(action) {
+                Ok(Command::Play(play)) => {
+                    // the plays without a layer have the rows of all the layers
+                    let play = match play.layer() {
+                        0 => Some(play),
+                        _ => play.unlayered(params.rows(), layers),
+                    };
+                    match play {
+                        Some(play)
+                            if play.cell().0 < rows
+                                && play.cell().1 < columns
+                                && !blocked(play.cell()) =>
+                        {
+                            Action::Play(play)
+                        }
+                        _ => return Err(invalid()),
+                    }
+                }
+                Ok(Command::Undo) => Action::Undo,
+                Ok(Command::Redo) => Action::Redo,
+                _ => return Err(invalid()),
+            };
+            events.push(Event { time, action });
+        }
+
+        match events.first().map(|event| event.action) {
+            Some(Action::Play(play))
+                if *play.mode() == PlayMode::Dig
+                    && layout.binary_search(&play.cell()).is_err()
+                    && !blocked(play.cell()) => {}
+            _ => return Err(ReplayError::InvalidStart),
+        }
+
+        let claims = match ended {
+            true => {
+                let result = match required::<String, _>(&mut lines, "result")?.as_str() {
+                    "won" => GameResult::Won,
+                    "lost" => GameResult::Lost,
+                    "continue" => GameResult::Continue,
+                    value => {
+                        return Err(ReplayError::InvalidField {
+                            field: "result",
+                            value: value.to_string(),
+                        })
+                    }
+                };
+                Some(Claims {
+                    result,
+                    time: Duration::from_millis(required(&mut lines, "time_ms")?),
+                    moves: required(&mut lines, "moves")?,
+                })
+            }
+            false => None,
+        };
+
+        return Ok(Replay {
+            params,
+            layout,
+            events,
+            claims,
+        });
+    }
+}
+
+/// Applies the plays of a replay one at a time.
+pub struct Player<'a> {
+    /// Replay being played
+    replay: &'a Replay,
+    /// Board after the plays applied so far
+    board: Board,
+    /// Number of plays applied
+    played: usize,
+}
+
+impl<'a> Player<'a> {
+    /// Constructor
+    ///
+    /// Starts with the board before the first play.
+    pub fn new(replay: &'a Replay) -> Player<'a> {
+        return Player {
+            replay,
+            board: replay.initial_board(),
+            played: 0,
+        };
+    }
+
+    /// Board getter
+    pub fn board(&self) -> &Board {
+        return &self.board;
+    }
+
+    /// Number of plays applied
+    pub fn played(&self) -> usize {
+        return self.played;
+    }
+
+    /// Next play to apply, `None` at the end of the replay.
+    pub fn next(&self) -> Option<&'a Event> {
+        return self.replay.events.get(self.played);
+    }
+
+    /// Time between the last applied play and the next one.
+    pub fn delay(&self) -> Option<Duration> {
+        let next = self.next()?;
+        return match self.played {
+            0 => Some(Duration::ZERO),
+            n => Some(next.time.saturating_sub(self.replay.events[n - 1].time)),
+        };
+    }
+
+    /// Applies the next play and returns it.
+    pub fn step(&mut self) -> Option<&'a Event> {
+        let event = self.next()?;
+        Replay::apply(&mut self.board, event, self.played == 0);
+        self.played += 1;
+        return Some(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::mask::Mask;
+    use crate::board::neighbourhood::Neighbourhood;
+    use crate::board::CellView;
+
+    /// Auxiliary function to write a replay to a string.
+    fn written(replay: &Replay) -> String {
+        let mut written = Vec::new();
+        replay.write(&mut written).unwrap();
+        return String::from_utf8(written).unwrap();
+    }
+
+    /// Auxiliary function to record a game on a small board:
+    ///  the first dig and a mark on a mine.
+    fn recorded(params: &Parameters, start: (u16, u16)) -> Replay {
+        let mut board = Board::new(params);
+        board.build(start);
+        let mut replay = Replay::record(&board, start);
+        let mine = replay.layout()[0];
+        board.mark(mine);
+        replay.push(&board, Action::Play(Play::new(PlayMode::Mark, mine)));
+        replay.finish(&board);
+        return replay;
+    }
+
+    /// Auxiliary function to replace the lines of a written replay.
+    fn edited(replay: &Replay, edit: impl FnOnce(&mut Vec<String>)) -> String {
+        let mut lines = written(replay)
+            .lines()
+            .map(str::to_string)
+            .collect::<Vec<String>>();
+        edit(&mut lines);
+        return lines.join("\n");
+    }
+
+    #[test]
+    fn written_replays_read_the_same() {
+        let mask = "xxxxx/xxxxx/xx.xx/xxxxx/xxxxx".parse::<Mask>().unwrap();
+        let params = Parameters::layered(5, 5, 2, 6)
+            .unwrap()
+            .with_neighbourhood(Neighbourhood::preset("cross").unwrap())
+            .unwrap()
+            .with_mask(mask)
+            .unwrap()
+            .with_seed(5);
+        let mut board = Board::new(&params);
+        board.build((0, 0));
+        let mut replay = Replay::record(&board, (0, 0));
+        // a cell of the second layer
+        let hidden = board
+            .cells()
+            .find(|cell| cell.0 >= 5 && board.cell_view(*cell) == CellView::Hidden)
+            .unwrap();
+        board.mark(hidden);
+        replay.push(&board, Action::Play(Play::new(PlayMode::Mark, hidden)));
+        board.undo();
+        replay.push(&board, Action::Undo);
+        replay.finish(&board);
+
+        let read = Replay::read(written(&replay).as_bytes()).unwrap();
+        assert_eq!(read.params(), replay.params());
+        assert_eq!(read.layout(), replay.layout());
+        // the times are written in milliseconds
+        assert_eq!(written(&read), written(&replay));
+        let actions = |replay: &Replay| {
+            return replay
+                .events()
+                .iter()
+                .map(|event| *event.action())
+                .collect::<Vec<Action>>();
+        };
+        assert_eq!(actions(&read), actions(&replay));
+        assert_eq!(read.claims().unwrap().result(), GameResult::Continue);
+    }
+
+    #[test]
+    fn other_versions_are_refused() {
+        let replay = recorded(&Parameters::new(4, 4, 2).unwrap().with_seed(1), (0, 0));
+        for version in ["0", "2", "7", "one"] {
+            let text = edited(&replay, |lines| {
+                lines[0] = format!("{} {}", MAGIC, version);
+            });
+            assert!(matches!(
+                Replay::read(text.as_bytes()),
+                Err(ReplayError::UnsupportedVersion(found)) if found == version
+            ));
+        }
+    }
+
+    #[test]
+    fn the_layout_must_match_the_variables() {
+        let replay = recorded(&Parameters::new(4, 4, 2).unwrap().with_seed(1), (0, 0));
+
+        let missing_row = edited(&replay, |lines| {
+            let layout = lines.iter().position(|line| line == "layout").unwrap();
+            lines.remove(layout + 1);
+        });
+        assert!(matches!(
+            Replay::read(missing_row.as_bytes()),
+            Err(ReplayError::WrongRowCount {
+                expected: 4,
+                found: 3
+            })
+        ));
+
+        let more_mines = edited(&replay, |lines| {
+            let mines = lines.iter().position(|line| line == "mines 2").unwrap();
+            lines[mines] = "mines 3".to_string();
+        });
+        assert!(matches!(
+            Replay::read(more_mines.as_bytes()),
+            Err(ReplayError::MinesMismatch {
+                declared: 3,
+                found: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn the_first_play_is_a_safe_dig() {
+        let replay = recorded(&Parameters::new(4, 4, 2).unwrap().with_seed(1), (0, 0));
+        let mine = replay.layout()[0];
+        let starts = [
+            format!("0 d {} {}", mine.0, mine.1),
+            "0 m 0 0".to_string(),
+            "0 undo".to_string(),
+        ];
+        for start in starts {
+            let text = edited(&replay, |lines| {
+                let plays = lines.iter().position(|line| line == "plays").unwrap();
+                lines[plays + 1] = start.clone();
+            });
+            assert!(
+                matches!(
+                    Replay::read(text.as_bytes()),
+                    Err(ReplayError::InvalidStart)
+                ),
+                "{}",
+                start
+            );
+        }
+    }
+}
@@ -0,0 +1,825 @@
+//! Saving and loading games to and from files.
+//!
+//! The save is a text file, starting with a line with its format
+//!  version, then a line for each game variable and at last the grid:
+//!
+//! ```text
+//! mineswipper save 1
+//! rows 2
+//! columns 3
+//! layers 1
+//! mines 1
+//! topology square
+//! neighbourhood none
+//! mask none
+//! wrap false
+//! seed 1234
+//! cells_to_dig 3
+//! marked 1
+//! deadly_mine none
+//! elapsed_ms 5000
+//! flag_limit none
+//! practice false
+//! practiced false
+//! clicks 3 1 0 0 1
+//! grid
+//! oo.
+//! F..
+//! ```
+//!
+//! Each cell of the grid is one of:
+//!  - `.`: undug cell without a mine
+//!  - `*`: undug cell with a mine
+//!  - `o`: dug cell
+//!  - `f`: marked cell without a mine
+//!  - `F`: marked cell with a mine
+//!  - `#`: cell blocked by the mask
+//!
+//! The neighbourhood is `none` when the numbers count the neighbours
+//!  of the topology, see [Neighbourhood] for the others.
+//!  The mask is `none` when all the cells can be played, see [Mask].
+//! On boards with layers the grid has the rows of all the layers,
+//!  one layer after another, as the [Board] numbers them.
+//! The clicks are the digs, marks, unmarks, chords and wasted clicks.
+//!
+//! The numbers are calculated from the mines and all the
+//!  variables are checked against the grid when loading, and
+//!  [validated](Parameters) as the ones of a new game.
+//! The history of plays is not saved, so plays done before
+//!  saving can't be undone after loading.
+//!
+//! The save is written to a temporary file next to it that then
+//!  replaces it, so a failed save doesn't lose the previous one.
+//!
+
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt::Formatter;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+use std::{fmt, io, process};
+
+use crate::board::history::History;
+use crate::board::mask::Mask;
+use crate::board::neighbourhood::Neighbourhood;
+use crate::board::summary::Clicks;
+use crate::board::topology::Topology;
+use crate::board::{Board, Cell, CellValue, GameResult};
+use crate::input::arguments::{InvalidParameters, Parameters};
+
+/// First line of every save file
+const MAGIC: &str = "mineswipper save";
+
+/// Current version of the save format
+const VERSION: u32 = 1;
+
+/// Game variables, on the order they are written
+const FIELDS: [&str; 17] = [
+    "rows",
+    "columns",
+    "layers",
+    "mines",
+    "topology",
+    "neighbourhood",
+    "mask",
+    "wrap",
+    "seed",
+    "cells_to_dig",
+    "marked",
+    "deadly_mine",
+    "elapsed_ms",
+    "flag_limit",
+    "practice",
+    "practiced",
+    "clicks",
+];
+
+/// Returned when a save can't be loaded
+#[derive(Debug)]
+pub enum LoadError {
+    /// The file can't be read
+    Io(io::Error),
+    /// The file doesn't start with the save header
+    NotASave,
+    /// The file was written by a newer version of the game
+    UnsupportedVersion(String),
+    /// A game variable is missing
+    MissingField(&'static str),
+    /// A game variable can't be parsed
+    InvalidField {
+        /// name of the variable
+        field: &'static str,
+        /// value on the file
+        value: String,
+    },
+    /// The game variables are invalid
+    Parameters(InvalidParameters),
+    /// The grid doesn't have the declared number of rows
+    WrongRowCount {
+        /// declared rows
+        expected: u16,
+        /// rows found
+        found: usize,
+    },
+    /// A row of the grid doesn't have the declared number of columns
+    WrongColumnCount {
+        /// row with the problem
+        row: usize,
+        /// declared columns
+        expected: u16,
+        /// columns found
+        found: usize,
+    },
+    /// A cell of the grid is not valid
+    InvalidCell {
+        /// row of the cell
+        row: usize,
+        /// column of the cell
+        column: usize,
+        /// character found
+        found: char,
+    },
+    /// The declared number of mines doesn't match the grid
+    MinesMismatch {
+        /// declared mines
+        declared: u32,
+        /// mines on the grid
+        found: u32,
+    },
+    /// The declared cells to dig don't match the grid
+    CellsToDigMismatch {
+        /// declared cells to dig
+        declared: u32,
+        /// undug cells without mines on the grid
+        found: u32,
+    },
+    /// The declared number of marks doesn't match the grid
+    MarkedMismatch {
+        /// declared marks
+        declared: u32,
+        /// marked cells on the grid
+        found: u32,
+    },
+    /// The mine that ended the game is not an undug mine of the grid
+    InvalidDeadlyMine(u16, u16),
+    /// A dug cell without adjacent mines has undug neighbours,
+    ///  which can't happen since they are dug together
+    UndugNextToEmpty(u16, u16),
+    /// There are more marks than the flag limit
+    FlagLimitExceeded,
+}
+
+impl Error for LoadError {}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            LoadError::Io(e) => write!(f, "Can't read the save: {}", e),
+            LoadError::NotASave => write!(f, "Not a save file"),
+            LoadError::UnsupportedVersion(v) => write!(f, "Unsupported save version {}", v),
+            LoadError::MissingField(field) => write!(f, "Missing {} on the save", field),
+            LoadError::InvalidField { field, value } => {
+                write!(f, "Invalid {} on the save: {:?}", field, value)
+            }
+            LoadError::Parameters(e) => e.fmt(f),
+            LoadError::WrongRowCount { expected, found } => {
+                write!(f, "Expected {} rows on the grid, found {}", expected, found)
+            }
+            LoadError::WrongColumnCount {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Expected {} columns on row {} of the grid, found {}",
+                expected, row, found
+            ),
+            LoadError::InvalidCell { row, column, found } => {
+                write!(f, "Invalid cell {:?} at {} {}", found, row, column)
+            }
+            LoadError::MinesMismatch { declared, found } => write!(
+                f,
+                "The save declares {} mines but the grid has {}",
+                declared, found
+            ),
+            LoadError::CellsToDigMismatch { declared, found } => write!(
+                f,
+                "The save declares {} cells to dig but the grid has {}",
+                declared, found
+            ),
+            LoadError::MarkedMismatch { declared, found } => write!(
+                f,
+                "The save declares {} marked cells but the grid has {}",
+                declared, found
+            ),
+            LoadError::InvalidDeadlyMine(row, column) => write!(
+                f,
+                "The mine that ended the game ({} {}) is not an undug mine",
+                row, column
+            ),
+            LoadError::UndugNextToEmpty(row, column) => write!(
+                f,
+                "The dug cell {} {} has no adjacent mines but has undug neighbours",
+                row, column
+            ),
+            LoadError::FlagLimitExceeded => write!(f, "There are more marks than the flag limit"),
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(e: io::Error) -> LoadError {
+        return LoadError::Io(e);
+    }
+}
+
+impl From<InvalidParameters> for LoadError {
+    fn from(e: InvalidParameters) -> LoadError {
+        return LoadError::Parameters(e);
+    }
+}
+
+/// Auxiliary function to write an optional value
+fn optional<T: fmt::Display>(value: Option<T>) -> String {
+    return match value {
+        Some(value) => value.to_string(),
+        None => "none".to_string(),
+    };
+}
+
+/// Auxiliary function to parse a game variable
+fn parse<T: std::str::FromStr>(field: &'static str, value: &str) -> Result<T, LoadError> {
+    return value.parse::<T>().map_err(|_| LoadError::InvalidField {
+        field,
+        value: value.to_string(),
+    });
+}
+
+/// Auxiliary function to parse an optional game variable
+fn parse_optional<T: std::str::FromStr>(
+    field: &'static str,
+    value: &str,
+) -> Result<Option<T>, LoadError> {
+    if value == "none" {
+        return Ok(None);
+    }
+    return parse(field, value).map(Some);
+}
+
+/// Auxiliary function to write a file without losing the previous
+///  one if something fails: the content is written to a temporary
+///  file on the same directory, which then replaces the file.
+pub(crate) fn write_atomically<P, F>(path: P, write: F) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    F: FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+{
+    let path = path.as_ref();
+    // unique, so games writing the same file don't share it
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(format!(".{}.tmp", process::id()));
+
+    let result = File::create(&temporary).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        write(&mut writer)?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+        return fs::rename(&temporary, path);
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    return result;
+}
+
+impl Board {
+    /// Writes the game to a file, see the [save](crate::board::save) format.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        return write_atomically(path, |writer| self.write_save(writer));
+    }
+
+    /// Writes the game in the [save](crate::board::save) format.
+    pub fn write_save<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "{} {}", MAGIC, VERSION)?;
+        writeln!(writer, "rows {}", self.rows)?;
+        writeln!(writer, "columns {}", self.columns)?;
+        writeln!(writer, "layers {}", self.layers)?;
+        writeln!(writer, "mines {}", self.mines_count)?;
+        writeln!(writer, "topology {}", self.topology)?;
+        writeln!(
+            writer,
+            "neighbourhood {}",
+            optional(self.neighbourhood.as_ref())
+        )?;
+        writeln!(writer, "mask {}", optional(self.mask.as_ref()))?;
+        writeln!(writer, "wrap {}", self.wrap)?;
+        writeln!(writer, "seed {}", optional(self.seed))?;
+        writeln!(writer, "cells_to_dig {}", self.cells_to_dig)?;
+        writeln!(writer, "marked {}", self.marked_count)?;
+        writeln!(
+            writer,
+            "deadly_mine {}",
+            optional(self.deadly_mine.map(|(r, c)| format!("{} {}", r, c)))
+        )?;
+        writeln!(writer, "elapsed_ms {}", self.elapsed().as_millis())?;
+        writeln!(writer, "flag_limit {}", optional(self.flag_limit))?;
+        writeln!(writer, "practice {}", self.practice)?;
+        writeln!(writer, "practiced {}", self.undid_game_over)?;
+        let clicks = &self.clicks;
+        writeln!(
+            writer,
+            "clicks {} {} {} {} {}",
+            clicks.digs, clicks.marks, clicks.unmarks, clicks.chords, clicks.wasted
+        )?;
+        writeln!(writer, "grid")?;
+
+        for (i, row) in self.board.iter().enumerate() {
+            let line = row
+                .iter()
+                .enumerate()
+                .map(|(j, cell)| {
+                    if self.is_blocked((i as u16, j as u16)) {
+                        return '#';
+                    }
+                    match (cell.value == CellValue::Mine, cell.dug, cell.marked) {
+                        (_, true, _) => 'o',
+                        (true, _, true) => 'F',
+                        (false, _, true) => 'f',
+                        (true, _, false) => '*',
+                        (false, _, false) => '.',
+                    }
+                })
+                .collect::<String>();
+            writeln!(writer, "{}", line)?;
+        }
+
+        return Ok(());
+    }
+
+    /// Loads a game from a file, see the [save](crate::board::save) format.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Board, LoadError> {
+        return Board::read_save(BufReader::new(File::open(path)?));
+    }
+
+    /// Reads a game in the [save](crate::board::save) format.
+    ///
+    /// Fails if anything on the save is inconsistent.
+    pub fn read_save<R: BufRead>(reader: R) -> Result<Board, LoadError> {
+        let mut lines = reader.lines();
+
+        let header = lines.next().ok_or(LoadError::NotASave)??;
+        let version = match header.strip_prefix(MAGIC) {
+            Some(version) => version.trim(),
+            None => return Err(LoadError::NotASave),
+        };
+        if version.parse::<u32>() != Ok(VERSION) {
+            return Err(LoadError::UnsupportedVersion(version.to_string()));
+        }
+
+        let mut fields = HashMap::new();
+        for line in lines.by_ref() {
+            let line = line?;
+            if line == "grid" {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(' ') {
+                fields.insert(name.to_string(), value.to_string());
+            }
+        }
+        let mut field = |name: &'static str| -> Result<String, LoadError> {
+            return fields.remove(name).ok_or(LoadError::MissingField(name));
+        };
+        let mut values = Vec::with_capacity(FIELDS.len());
+        for name in FIELDS.iter() {
+            values.push(field(name)?);
+        }
+
+        let rows: u16 = parse("rows", &values[0])?;
+        let columns: u16 = parse("columns", &values[1])?;
+        let layers: u16 = parse("layers", &values[2])?;
+        let mines_count: u32 = parse("mines", &values[3])?;
+        let topology: Topology = parse("topology", &values[4])?;
+        let neighbourhood: Option<Neighbourhood> = parse_optional("neighbourhood", &values[5])?;
+        let mask: Option<Mask> = parse_optional("mask", &values[6])?;
+        let wrap: bool = parse("wrap", &values[7])?;
+        let seed: Option<u64> = parse_optional("seed", &values[8])?;
+        let cells_to_dig: u32 = parse("cells_to_dig", &values[9])?;
+        let marked_count: u32 = parse("marked", &values[10])?;
+        let deadly_mine = match values[11].as_str() {
+            "none" => None,
+            value => match value.split_once(' ') {
+                Some((r, c)) => Some((parse("deadly_mine", r)?, parse("deadly_mine", c)?)),
+                None => return Err(parse::<u16>("deadly_mine", value).unwrap_err()),
+            },
+        };
+        let elapsed_ms: u64 = parse("elapsed_ms", &values[12])?;
+        let flag_limit: Option<u32> = parse_optional("flag_limit", &values[13])?;
+        let practice: bool = parse("practice", &values[14])?;
+        let undid_game_over: bool = parse("practiced", &values[15])?;
+        let counts = values[16]
+            .split_whitespace()
+            .map(|count| parse::<u32>("clicks", count))
+            .collect::<Result<Vec<u32>, LoadError>>()?;
+        let clicks = match counts[..] {
+            [digs, marks, unmarks, chords, wasted] => Clicks {
+                digs,
+                marks,
+                unmarks,
+                chords,
+                wasted,
+            },
+            _ => {
+                return Err(LoadError::InvalidField {
+                    field: "clicks",
+                    value: values[16].clone(),
+                })
+            }
+        };
+
+        // the same checks as the variables of a new game
+        let params =
+            Parameters::layered(rows, columns, layers, mines_count)?.with_topology(topology)?;
+        let params = match neighbourhood {
+            Some(neighbourhood) => params.with_neighbourhood(neighbourhood)?,
+            None => params,
+        };
+        let params = match mask {
+            Some(mask) => params.with_mask(mask)?,
+            None => params,
+        };
+        let params = match wrap {
+            true => params.with_wrap()?,
+            false => params,
+        };
+        let neighbourhood = params.neighbourhood().cloned();
+        let mask = params.mask().cloned();
+        // the rows of all the layers, checked by the parameters
+        let grid_rows = rows * layers;
+        let blocked = |i: usize, j: usize| {
+            mask.as_ref()
+                .is_some_and(|mask| mask.is_blocked(i as u16 % rows, j as u16))
+        };
+
+        let mut board = Vec::with_capacity(grid_rows as usize);
+        let mut mines = HashSet::new();
+        let mut found_to_dig = 0;
+        let mut found_marked = 0;
+        for (i, line) in lines.enumerate() {
+            let line = line?;
+            if i >= grid_rows as usize {
+                // allow trailing empty lines
+                if line.trim().is_empty() {
+                    continue;
+                }
+                return Err(LoadError::WrongRowCount {
+                    expected: grid_rows,
+                    found: i + 1,
+                });
+            }
+
+            let chars = line.trim_end().chars().collect::<Vec<char>>();
+            if chars.len() != columns as usize {
+                return Err(LoadError::WrongColumnCount {
+                    row: i,
+                    expected: columns,
+                    found: chars.len(),
+                });
+            }
+
+            let mut row = Vec::with_capacity(columns as usize);
+            for (j, c) in chars.into_iter().enumerate() {
+                // the blocked cells must be the ones of the mask
+                if (c == '#') != blocked(i, j) {
+                    return Err(LoadError::InvalidCell {
+                        row: i,
+                        column: j,
+                        found: c,
+                    });
+                }
+                let (mine, dug, marked) = match c {
+                    '.' | '#' => (false, false, false),
+                    '*' => (true, false, false),
+                    'o' => (false, true, false),
+                    'f' => (false, false, true),
+                    'F' => (true, false, true),
+                    found => {
+                        return Err(LoadError::InvalidCell {
+                            row: i,
+                            column: j,
+                            found,
+                        })
+                    }
+                };
+                if mine {
+                    mines.insert((i as u16, j as u16));
+                } else if !dug && c != '#' {
+                    found_to_dig += 1;
+                }
+                if marked {
+                    found_marked += 1;
+                }
+                row.push(Cell {
+                    dug,
+                    marked,
+                    value: match mine {
+                        true => CellValue::Mine,
+                        false => CellValue::Empty,
+                    },
+                });
+            }
+            board.push(row);
+        }
+
+        if board.len() != grid_rows as usize {
+            return Err(LoadError::WrongRowCount {
+                expected: grid_rows,
+                found: board.len(),
+            });
+        }
+        if mines.len() as u32 != mines_count {
+            return Err(LoadError::MinesMismatch {
+                declared: mines_count,
+                found: mines.len() as u32,
+            });
+        }
+        if found_to_dig != cells_to_dig {
+            return Err(LoadError::CellsToDigMismatch {
+                declared: cells_to_dig,
+                found: found_to_dig,
+            });
+        }
+        if found_marked != marked_count {
+            return Err(LoadError::MarkedMismatch {
+                declared: marked_count,
+                found: found_marked,
+            });
+        }
+        if flag_limit.is_some_and(|limit| marked_count > limit) {
+            return Err(LoadError::FlagLimitExceeded);
+        }
+        if let Some((row, column)) = deadly_mine {
+            let is_undug_mine = board
+                .get(row as usize)
+                .and_then(|cells| cells.get(column as usize))
+                .is_some_and(|cell| cell.value == CellValue::Mine && !cell.marked);
+            if !is_undug_mine {
+                return Err(LoadError::InvalidDeadlyMine(row, column));
+            }
+        }
+
+        let mut board = Board {
+            rows,
+            columns,
+            layers,
+            mines_count,
+            topology,
+            neighbourhood,
+            mask,
+            wrap,
+            cells_to_dig,
+            opening: 0,
+            deadly_mine,
+            seed,
+            no_guess: None,
+            attempts: 0,
+            guess_free: false,
+            marked_count,
+            flag_limit,
+            history: History::default(),
+            changes: None,
+            practice,
+            undid_game_over,
+            started: None,
+            elapsed_before: Duration::from_millis(elapsed_ms),
+            clicks,
+            board,
+        };
+        board.place_numbers(mines);
+
+        for (row, column) in board.cells() {
+            let cell = &board.board[row as usize][column as usize];
+            if !cell.dug || cell.value != CellValue::Empty {
+                continue;
+            }
+            let undug_neighbour = board
+                .generate_ring((row, column))
+                .any(|(r, c)| !board.board[r as usize][c as usize].dug);
+            if undug_neighbour {
+                return Err(LoadError::UndugNextToEmpty(row, column));
+            }
+        }
+
+        if board.result() == GameResult::Continue {
+            board.resume();
+        }
+        return Ok(board);
+    }
+}
+
+/// Auxiliary function for the tests to write the save of a game being
+///  played on a square board with the given grid, the other variables
+///  are counted on it.
+#[cfg(test)]
+pub(crate) fn grid_save(grid: &[&str]) -> String {
+    let count = |cells: &str| {
+        return grid
+            .iter()
+            .flat_map(|row| row.chars())
+            .filter(|c| cells.contains(*c))
+            .count();
+    };
+    let mut save = format!(
+        "{} {}\nrows {}\ncolumns {}\nlayers 1\nmines {}\ntopology square\n\
+         neighbourhood none\nmask none\nwrap false\nseed none\ncells_to_dig {}\n\
+         marked {}\ndeadly_mine none\nelapsed_ms 0\nflag_limit none\n\
+         practice false\npracticed false\nclicks 0 0 0 0 0\ngrid\n",
+        MAGIC,
+        VERSION,
+        grid.len(),
+        grid[0].len(),
+        count("*F"),
+        count(".f"),
+        count("fF"),
+    );
+    for row in grid {
+        save.push_str(row);
+        save.push('\n');
+    }
+    return save;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{from_grid, CellView};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// Auxiliary function to load a save, with the lines starting
+    ///  with a field name replaced by the given ones.
+    fn load_with(grid: &[&str], fields: &[&str]) -> Result<Board, LoadError> {
+        let save = grid_save(grid)
+            .lines()
+            .map(|line| {
+                let name = line.split(' ').next().unwrap();
+                match fields
+                    .iter()
+                    .find(|field| field.split(' ').next() == Some(name))
+                {
+                    Some(field) => field.to_string(),
+                    None => line.to_string(),
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        return Board::read_save(save.as_bytes());
+    }
+
+    /// Auxiliary function to write a save to a string,
+    ///  without the time that keeps running.
+    fn written(board: &Board) -> String {
+        let mut save = Vec::new();
+        board.write_save(&mut save).unwrap();
+        return String::from_utf8(save)
+            .unwrap()
+            .lines()
+            .filter(|line| !line.starts_with("elapsed_ms"))
+            .collect::<Vec<&str>>()
+            .join("\n");
+    }
+
+    #[test]
+    fn saved_games_load_the_same() {
+        let params = Parameters::new(9, 9, 10).unwrap().with_flag_limit(12);
+        let mut board = Board::new(&params);
+        board.build_with_rng((4, 4), &mut StdRng::seed_from_u64(3));
+        let cells = (0..9)
+            .flat_map(|row| (0..9).map(move |column| (row, column)))
+            .collect::<Vec<(u16, u16)>>();
+        let hidden = cells
+            .iter()
+            .copied()
+            .filter(|cell| board.cell_view(*cell) == CellView::Hidden)
+            .collect::<Vec<(u16, u16)>>();
+        board.mark(hidden[0]);
+
+        let elapsed = board.elapsed();
+        let mut save = Vec::new();
+        board.write_save(&mut save).unwrap();
+        let loaded = Board::read_save(&save[..]).unwrap();
+        assert_eq!(written(&loaded), written(&board));
+        assert!(loaded.elapsed().as_millis() >= elapsed.as_millis());
+        for cell in cells {
+            assert_eq!(loaded.cell_view(cell), board.cell_view(cell));
+        }
+        assert_eq!(loaded.cells_to_dig(), board.cells_to_dig());
+        assert_eq!(loaded.mines_left(), board.mines_left());
+        assert_eq!(loaded.flags_left(), Some(11));
+        assert_eq!(loaded.result(), GameResult::Continue);
+    }
+
+    #[test]
+    fn unknown_versions_are_refused() {
+        let save = grid_save(&["oo", "*."]).replacen(&VERSION.to_string(), "99", 1);
+        assert!(matches!(
+            Board::read_save(save.as_bytes()),
+            Err(LoadError::UnsupportedVersion(version)) if version == "99"
+        ));
+        assert!(matches!(
+            Board::read_save("not a save".as_bytes()),
+            Err(LoadError::NotASave)
+        ));
+    }
+
+    #[test]
+    fn counters_must_match_the_grid() {
+        assert!(load_with(&["oo", "*."], &[]).is_ok());
+        assert!(matches!(
+            load_with(&["oo", "*."], &["cells_to_dig 2"]),
+            Err(LoadError::CellsToDigMismatch {
+                declared: 2,
+                found: 1
+            })
+        ));
+        assert!(matches!(
+            load_with(&["oo", "*."], &["mines 2"]),
+            Err(LoadError::MinesMismatch {
+                declared: 2,
+                found: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn variables_are_checked_as_a_new_game() {
+        assert!(matches!(
+            load_with(
+                &["oo", "*."],
+                &["topology hexagonal", "neighbourhood cross"]
+            ),
+            Err(LoadError::Parameters(
+                InvalidParameters::NeighbourhoodNotSquare
+            ))
+        ));
+        assert!(matches!(
+            load_with(&["oo", "*."], &["wrap true"]),
+            Err(LoadError::Parameters(InvalidParameters::TooSmallToWrap {
+                minimum: 3
+            }))
+        ));
+    }
+
+    #[test]
+    fn empty_cells_have_their_neighbours_dug() {
+        // 0,0 has no mines around but 1,1 is undug
+        assert!(matches!(
+            load_with(&["oo.", "o..", "..*"], &[]),
+            Err(LoadError::UndugNextToEmpty(0, 0))
+        ));
+    }
+
+    #[test]
+    fn the_deadly_mine_is_an_undug_mine() {
+        assert!(load_with(&["oo", "*."], &["deadly_mine 1 0"]).is_ok());
+        // a safe cell, a dug cell and a cell outside of the board
+        assert!(matches!(
+            load_with(&["oo", "*."], &["deadly_mine 1 1"]),
+            Err(LoadError::InvalidDeadlyMine(1, 1))
+        ));
+        assert!(matches!(
+            load_with(&["oo", "*."], &["deadly_mine 0 0"]),
+            Err(LoadError::InvalidDeadlyMine(0, 0))
+        ));
+        assert!(matches!(
+            load_with(&["oo", "*."], &["deadly_mine 5 5"]),
+            Err(LoadError::InvalidDeadlyMine(5, 5))
+        ));
+    }
+
+    #[test]
+    fn failed_saves_keep_the_previous_one() {
+        let path = std::env::temp_dir().join(format!("mineswipper-save-{}", process::id()));
+        let board = from_grid(&["oo", "*."]);
+        fs::write(&path, "previous").unwrap();
+
+        let failed = write_atomically(&path, |writer| {
+            writer.write_all(b"half a save")?;
+            return Err(io::Error::other("disk full"));
+        });
+        assert!(failed.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "previous");
+
+        board.save(&path).unwrap();
+        let loaded = Board::load(&path).unwrap();
+        assert_eq!(written(&loaded), written(&board));
+        let directory = fs::read_dir(std::env::temp_dir()).unwrap();
+        let name = path.file_name().unwrap().to_str().unwrap();
+        assert!(!directory.flatten().any(|entry| entry
+            .file_name()
+            .to_string_lossy()
+            .starts_with(&format!("{}.", name))));
+        fs::remove_file(&path).unwrap();
+    }
+}
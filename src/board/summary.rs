@@ -0,0 +1,189 @@
+//! Statistics of a game, shown when it ends.
+
+use std::fmt;
+use std::fmt::Formatter;
+use std::time::Duration;
+
+use crate::board::benchmark::Benchmark;
+use crate::board::GameResult;
+use crate::input::plays::PlayMode;
+
+/// Number of plays done by the player, by play mode.
+///
+/// Only plays done while the game is running are counted,
+///  undo doesn't remove them.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub struct Clicks {
+    /// Digs, including the first play
+    pub(super) digs: u32,
+    /// Marks
+    pub(super) marks: u32,
+    /// Unmarks
+    pub(super) unmarks: u32,
+    /// Chords
+    pub(super) chords: u32,
+    /// Digs and chords that didn't dig anything,
+    ///  ex: digging an already dug or a marked cell
+    pub(super) wasted: u32,
+}
+
+impl Clicks {
+    /// Auxiliary method to count a play.
+    pub(super) fn count(&mut self, mode: PlayMode, changed: bool) {
+        match mode {
+            PlayMode::Dig => self.digs += 1,
+            PlayMode::Mark => self.marks += 1,
+            PlayMode::Unmark => self.unmarks += 1,
+            PlayMode::Chord => self.chords += 1,
+        }
+        if !changed && (mode == PlayMode::Dig || mode == PlayMode::Chord) {
+            self.wasted += 1;
+        }
+    }
+
+    /// Digs getter
+    pub fn digs(&self) -> u32 {
+        return self.digs;
+    }
+
+    /// Marks getter
+    pub fn marks(&self) -> u32 {
+        return self.marks;
+    }
+
+    /// Unmarks getter
+    pub fn unmarks(&self) -> u32 {
+        return self.unmarks;
+    }
+
+    /// Chords getter
+    pub fn chords(&self) -> u32 {
+        return self.chords;
+    }
+
+    /// Wasted clicks getter
+    pub fn wasted(&self) -> u32 {
+        return self.wasted;
+    }
+
+    /// All the plays
+    pub fn total(&self) -> u32 {
+        return self.digs + self.marks + self.unmarks + self.chords;
+    }
+}
+
+/// Returned by [finish](crate::board::Board::finish).
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Summary {
+    /// How the game ended
+    result: GameResult,
+    /// Time from the first play until the game ended
+    time: Duration,
+    /// Plays done
+    clicks: Clicks,
+    /// Cells without mine dug
+    cells_cleared: u32,
+    /// 3BV of the board
+    benchmark: Benchmark,
+}
+
+impl Summary {
+    /// Constructor
+    pub(super) fn new(
+        result: GameResult,
+        time: Duration,
+        clicks: Clicks,
+        cells_cleared: u32,
+        benchmark: Benchmark,
+    ) -> Summary {
+        return Summary {
+            result,
+            time,
+            clicks,
+            cells_cleared,
+            benchmark,
+        };
+    }
+
+    /// Result getter
+    pub fn result(&self) -> GameResult {
+        return self.result;
+    }
+
+    /// Time getter
+    pub fn time(&self) -> Duration {
+        return self.time;
+    }
+
+    /// Clicks getter
+    pub fn clicks(&self) -> &Clicks {
+        return &self.clicks;
+    }
+
+    /// Cells cleared getter
+    pub fn cells_cleared(&self) -> u32 {
+        return self.cells_cleared;
+    }
+
+    /// 3BV getter
+    pub fn benchmark(&self) -> &Benchmark {
+        return &self.benchmark;
+    }
+
+    /// Solved 3BV per second.
+    ///
+    /// `None` if no time has passed.
+    pub fn bbbv_per_second(&self) -> Option<f64> {
+        if self.time.is_zero() {
+            return None;
+        }
+        return Some(self.benchmark.solved_bbbv() as f64 / self.time.as_secs_f64());
+    }
+
+    /// Solved 3BV divided by the clicks, 1 is the best
+    ///  possible without chords.
+    ///
+    /// `None` if there are no clicks.
+    pub fn efficiency(&self) -> Option<f64> {
+        if self.clicks.total() == 0 {
+            return None;
+        }
+        return Some(self.benchmark.solved_bbbv() as f64 / self.clicks.total() as f64);
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let clicks = &self.clicks;
+        writeln!(f, "time: {:.1}s", self.time.as_secs_f64())?;
+        writeln!(
+            f,
+            "clicks: {} ({} digs, {} chords, {} marks, {} unmarks)",
+            clicks.total(),
+            clicks.digs,
+            clicks.chords,
+            clicks.marks,
+            clicks.unmarks
+        )?;
+        writeln!(f, "wasted clicks: {}", clicks.wasted)?;
+        writeln!(f, "cells cleared: {}", self.cells_cleared)?;
+
+        let benchmark = &self.benchmark;
+        writeln!(
+            f,
+            "3BV: {}/{} ({} openings, {} isolated numbers in {} islands)",
+            benchmark.solved_bbbv(),
+            benchmark.bbbv(),
+            benchmark.openings(),
+            benchmark.isolated_numbers(),
+            benchmark.islands()
+        )?;
+        if let Some(bbbv_per_second) = self.bbbv_per_second() {
+            writeln!(f, "3BV/s: {:.2}", bbbv_per_second)?;
+        }
+        match self.efficiency() {
+            Some(efficiency) => write!(f, "efficiency: {:.0}%", efficiency * 100.0),
+            None => write!(f, "efficiency: -"),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! Shapes of the cells of a board and which cells are next to each other.
+//!
+//! Every part of the game that needs the neighbours of a cell (numbers,
+//!  flood fill, chords, the solver...) gets them from the [Topology]
+//!  of the board, so new shapes only need to be described here.
+//!
+//! Cells are always addressed by `(row, column)`:
+//!  - [Square](Topology::Square): the classic grid, with 8 neighbours;
+//!  - [Hexagonal](Topology::Hexagonal): hexagons on rows, with the odd
+//!    rows shifted half a cell to the right, so each cell has 6 neighbours:
+//!
+//! ```text
+//!  0 0 0 0
+//!   1 1 1 1
+//!  2 2 2 2
+//! ```
+//!
+
+use std::fmt;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+/// Neighbours of a square cell, ordered by row and then column
+const SQUARE: [(i32, i32); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// Directions the empty area of the first play grows on a square board
+const SQUARE_EXPANSION: [(i32, i32); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+
+/// Neighbours of a hexagonal cell on an even row
+const HEXAGONAL_EVEN: [(i32, i32); 6] = [(-1, -1), (-1, 0), (0, -1), (0, 1), (1, -1), (1, 0)];
+
+/// Neighbours of a hexagonal cell on an odd row, which is shifted to the right
+const HEXAGONAL_ODD: [(i32, i32); 6] = [(-1, 0), (-1, 1), (0, -1), (0, 1), (1, 0), (1, 1)];
+
+/// Shape of the cells of a board
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Topology {
+    /// Square cells with 8 neighbours
+    Square,
+    /// Hexagonal cells with 6 neighbours, odd rows are shifted to the right
+    Hexagonal,
+}
+
+impl Topology {
+    /// Names accepted by [from_str](Topology::from_str)
+    pub const NAMES: [&'static str; 2] = ["square", "hexagonal"];
+
+    /// Row and column differences from a cell on the given row
+    ///  to each of its neighbours.
+    ///
+    /// Some of them can be outside of the board.
+    pub fn neighbour_offsets(&self, row: u16) -> &'static [(i32, i32)] {
+        return match self {
+            Topology::Square => &SQUARE,
+            Topology::Hexagonal if row.is_multiple_of(2) => &HEXAGONAL_EVEN,
+            Topology::Hexagonal => &HEXAGONAL_ODD,
+        };
+    }
+
+    /// Directions the empty area of the first play can grow from a
+    ///  cell on the given row.
+    ///
+    /// On square boards it only grows on straight lines, so the
+    ///  area is the same as before there were other topologies
+    ///  and old seeds still generate the same boards.
+    pub fn expansion_offsets(&self, row: u16) -> &'static [(i32, i32)] {
+        return match self {
+            Topology::Square => &SQUARE_EXPANSION,
+            Topology::Hexagonal => self.neighbour_offsets(row),
+        };
+    }
+
+    /// If the cells of the row are drawn half a cell to the right.
+    pub fn is_shifted(&self, row: u16) -> bool {
+        return *self == Topology::Hexagonal && row % 2 == 1;
+    }
+}
+
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Topology::Square => write!(f, "square"),
+            Topology::Hexagonal => write!(f, "hexagonal"),
+        }
+    }
+}
+
+/// Returned when the name of a topology is not known
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct UnknownTopology(pub String);
+
+impl fmt::Display for UnknownTopology {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Unknown topology {:?}, expected one of: {}",
+            self.0,
+            Topology::NAMES.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for UnknownTopology {}
+
+impl FromStr for Topology {
+    type Err = UnknownTopology;
+
+    fn from_str(name: &str) -> Result<Topology, UnknownTopology> {
+        return match name {
+            "square" => Ok(Topology::Square),
+            "hexagonal" => Ok(Topology::Hexagonal),
+            _ => Err(UnknownTopology(name.to_string())),
+        };
+    }
+}
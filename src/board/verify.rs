@@ -0,0 +1,362 @@
+//! Checks that a [replay](crate::board::replay) is a real game.
+//!
+//! The plays are applied again to a board with the recorded layout,
+//!  and the result, time and number of moves are compared with the
+//!  ones claimed by the replay. The layout itself is checked by
+//!  building the board again from the seed and the first play.
+//!
+
+use std::fmt;
+use std::fmt::Formatter;
+use std::time::Duration;
+
+use crate::board::replay::{Action, Replay};
+use crate::board::{Board, GameResult};
+
+/// Something wrong found on a replay
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Issue {
+    /// The replay doesn't say how the game ended
+    MissingClaims,
+    /// The replay has no seed, so the layout can't be checked
+    NoSeed,
+    /// The layout is not the one generated by the seed
+    TamperedLayout,
+    /// The time of a play is before the one of the previous play,
+    ///  or the first play is not at time 0
+    InvalidTime(usize),
+    /// A play was done after the game ended, outside of practice mode
+    AfterGameOver(usize),
+    /// Undo was used after the game ended, so it doesn't count
+    Practice,
+    /// The claimed result is not the one of the plays
+    ResultMismatch {
+        /// result on the replay
+        claimed: GameResult,
+        /// result of the plays
+        actual: GameResult,
+    },
+    /// The claimed time is not the one of the plays
+    TimeMismatch {
+        /// time on the replay
+        claimed: Duration,
+        /// time of the plays
+        actual: Duration,
+    },
+    /// The claimed number of moves is not the one of the plays
+    MovesMismatch {
+        /// moves on the replay
+        claimed: usize,
+        /// number of plays
+        actual: usize,
+    },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Issue::MissingClaims => write!(f, "The replay doesn't say how the game ended"),
+            Issue::NoSeed => write!(f, "The replay has no seed, the layout can't be checked"),
+            Issue::TamperedLayout => write!(f, "The layout doesn't match the seed"),
+            Issue::InvalidTime(index) => write!(f, "Play {} goes back in time", index),
+            Issue::AfterGameOver(index) => {
+                write!(f, "Play {} was done after the game ended", index)
+            }
+            Issue::Practice => write!(f, "Undo was used after the game ended"),
+            Issue::ResultMismatch { claimed, actual } => write!(
+                f,
+                "The replay claims {:?} but the plays result in {:?}",
+                claimed, actual
+            ),
+            Issue::TimeMismatch { claimed, actual } => write!(
+                f,
+                "The replay claims {:.3}s but the plays took {:.3}s",
+                claimed.as_secs_f64(),
+                actual.as_secs_f64()
+            ),
+            Issue::MovesMismatch { claimed, actual } => {
+                write!(f, "The replay claims {} moves but has {}", claimed, actual)
+            }
+        }
+    }
+}
+
+/// What applying the plays of a replay again resulted in.
+#[derive(Debug)]
+pub struct Verification {
+    /// Result after all the plays
+    result: GameResult,
+    /// Time of the play that ended the game,
+    ///  or of the last play if it didn't end
+    time: Duration,
+    /// Number of plays
+    moves: usize,
+    /// Everything wrong found
+    issues: Vec<Issue>,
+}
+
+/// Just getters
+impl Verification {
+    /// Result getter
+    pub fn result(&self) -> GameResult {
+        return self.result;
+    }
+
+    /// Time getter
+    pub fn time(&self) -> Duration {
+        return self.time;
+    }
+
+    /// Moves getter
+    pub fn moves(&self) -> usize {
+        return self.moves;
+    }
+
+    /// Issues getter
+    pub fn issues(&self) -> &[Issue] {
+        return &self.issues;
+    }
+
+    /// If nothing wrong was found
+    pub fn is_valid(&self) -> bool {
+        return self.issues.is_empty();
+    }
+}
+
+impl fmt::Display for Verification {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "result: {:?}", self.result)?;
+        writeln!(f, "time: {:.3}s", self.time.as_secs_f64())?;
+        writeln!(f, "moves: {}", self.moves)?;
+        if self.is_valid() {
+            return write!(f, "The replay is valid");
+        }
+        write!(f, "The replay is not valid:")?;
+        for issue in self.issues.iter() {
+            write!(f, "\n - {}", issue)?;
+        }
+        return Ok(());
+    }
+}
+
+/// Auxiliary function to check the layout against the seed.
+fn check_layout(replay: &Replay, issues: &mut Vec<Issue>) {
+    if replay.params().seed().is_none() {
+        issues.push(Issue::NoSeed);
+        return;
+    }
+
+    // the reader guarantees the first play is a dig
+    let start = match replay.events()[0].action() {
+        Action::Play(play) => play.cell(),
+        _ => unreachable!(),
+    };
+    let mut board = Board::new(replay.params());
+    board.build(start);
+    if Replay::record(&board, start).layout() != replay.layout() {
+        issues.push(Issue::TamperedLayout);
+    }
+}
+
+/// Applies the plays of the replay again and
+///  compares them with the claims of the replay.
+pub fn verify(replay: &Replay) -> Verification {
+    let mut issues = Vec::new();
+    check_layout(replay, &mut issues);
+
+    let mut board = replay.initial_board();
+    let mut previous = Duration::ZERO;
+    let mut end = None;
+    for (index, event) in replay.events().iter().enumerate() {
+        if event.time() < previous || (index == 0 && event.time() != Duration::ZERO) {
+            issues.push(Issue::InvalidTime(index));
+        }
+        previous = event.time();
+
+        let before = board.result();
+        if before != GameResult::Continue && !replay.params().practice() {
+            issues.push(Issue::AfterGameOver(index));
+        }
+
+        let after = Replay::apply(&mut board, event, index == 0);
+        if after != before {
+            end = match after {
+                GameResult::Continue => None,
+                _ => Some(event.time()),
+            };
+        }
+    }
+
+    if board.is_practice() {
+        issues.push(Issue::Practice);
+    }
+
+    let result = board.result();
+    let moves = replay.events().len();
+    // there is always a first play
+    let time = end.unwrap_or(previous);
+
+    match replay.claims() {
+        None => issues.push(Issue::MissingClaims),
+        Some(claims) => {
+            if claims.result() != result {
+                issues.push(Issue::ResultMismatch {
+                    claimed: claims.result(),
+                    actual: result,
+                });
+            }
+            // an unfinished game can be saved some time after the last play
+            let time_matches = match result {
+                GameResult::Continue => claims.time() >= time,
+                _ => claims.time() == time,
+            };
+            if !time_matches {
+                issues.push(Issue::TimeMismatch {
+                    claimed: claims.time(),
+                    actual: time,
+                });
+            }
+            if claims.moves() != moves {
+                issues.push(Issue::MovesMismatch {
+                    claimed: claims.moves(),
+                    actual: moves,
+                });
+            }
+        }
+    }
+
+    return Verification {
+        result,
+        time,
+        moves,
+        issues,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::mask::Mask;
+    use crate::board::replay::ReplayError;
+    use crate::input::arguments::Parameters;
+    use crate::input::plays::{Play, PlayMode};
+
+    /// Auxiliary function to write the replay of a lost game:
+    ///  the first dig, a mark on a mine and a dig on another mine.
+    fn lost_game() -> String {
+        let params = Parameters::new(9, 9, 10).unwrap().with_seed(7);
+        let mut board = Board::new(&params);
+        board.build((4, 4));
+        let mut replay = Replay::record(&board, (4, 4));
+        let (mine, other) = (replay.layout()[0], replay.layout()[1]);
+
+        board.mark(mine);
+        replay.push(&board, Action::Play(Play::new(PlayMode::Mark, mine)));
+        board.dig(other);
+        replay.push(&board, Action::Play(Play::new(PlayMode::Dig, other)));
+        replay.finish(&board);
+
+        let mut written = Vec::new();
+        replay.write(&mut written).unwrap();
+        return String::from_utf8(written).unwrap();
+    }
+
+    /// Auxiliary function to verify a replay written to a string.
+    fn issues(replay: &str) -> Vec<Issue> {
+        let replay = Replay::read(replay.as_bytes()).unwrap();
+        return verify(&replay).issues().to_vec();
+    }
+
+    /// Auxiliary function to replace the value of a field of a replay.
+    fn with_field(replay: &str, field: &str, value: &str) -> String {
+        return replay
+            .lines()
+            .map(|line| match line.split_once(' ') {
+                Some((name, _)) if name == field => format!("{} {}", field, value),
+                _ => line.to_string(),
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+    }
+
+    #[test]
+    fn recorded_games_are_valid() {
+        let replay = Replay::read(lost_game().as_bytes()).unwrap();
+        let verification = verify(&replay);
+        assert!(verification.is_valid(), "{}", verification);
+        assert_eq!(verification.result(), GameResult::Lost);
+        assert_eq!(verification.moves(), 3);
+    }
+
+    #[test]
+    fn tampered_layouts_are_found() {
+        // a mine moved to a safe cell of its row, away from the first dig
+        let replay = lost_game();
+        let mut lines = replay.lines().map(str::to_string).collect::<Vec<String>>();
+        let first = lines.iter().position(|line| line == "layout").unwrap() + 1;
+        let row = (first..first + 9)
+            .find(|row| lines[*row].contains('*') && lines[*row].contains('.') && *row != first + 4)
+            .unwrap();
+        let mine = lines[row].find('*').unwrap();
+        let safe = lines[row].find('.').unwrap();
+        let mut cells = lines[row].chars().collect::<Vec<char>>();
+        cells.swap(mine, safe);
+        lines[row] = cells.into_iter().collect();
+
+        assert!(issues(&lines.join("\n")).contains(&Issue::TamperedLayout));
+    }
+
+    #[test]
+    fn wrong_claims_are_found() {
+        let replay = lost_game();
+        assert!(matches!(
+            issues(&with_field(&replay, "result", "won"))[..],
+            [Issue::ResultMismatch {
+                claimed: GameResult::Won,
+                actual: GameResult::Lost
+            }]
+        ));
+
+        let time = replay
+            .lines()
+            .find_map(|line| line.strip_prefix("time_ms "))
+            .unwrap()
+            .parse::<u64>()
+            .unwrap();
+        let later = (time + 1000).to_string();
+        assert!(matches!(
+            issues(&with_field(&replay, "time_ms", &later))[..],
+            [Issue::TimeMismatch { .. }]
+        ));
+
+        assert_eq!(
+            issues(&with_field(&replay, "moves", "4")),
+            vec![Issue::MovesMismatch {
+                claimed: 4,
+                actual: 3
+            }]
+        );
+    }
+
+    #[test]
+    fn plays_on_blocked_cells_are_refused() {
+        let mask = "xxx/x.x/xxx".parse::<Mask>().unwrap();
+        let params = Parameters::new(3, 3, 1)
+            .unwrap()
+            .with_mask(mask)
+            .unwrap()
+            .with_seed(1);
+        let mut board = Board::new(&params);
+        board.build((0, 0));
+        let mut replay = Replay::record(&board, (0, 0));
+        replay.push(&board, Action::Play(Play::new(PlayMode::Mark, (1, 1))));
+        replay.finish(&board);
+
+        let mut written = Vec::new();
+        replay.write(&mut written).unwrap();
+        assert!(matches!(
+            Replay::read(&written[..]),
+            Err(ReplayError::InvalidEvent { index: 1, .. })
+        ));
+    }
+}
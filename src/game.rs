@@ -0,0 +1,351 @@
+//! The play pipeline shared by the frontends.
+//!
+//! A [Session] owns the [Board] of the game being played and applies
+//!  the [commands](Command) of the player to it, checking them first.
+//! It also records the game to a replay file and, when it ends,
+//!  to the [statistics](crate::stats).
+//!
+//! What the frontends should tell the player is returned as a [Notice].
+//!
+
+use std::fmt;
+use std::fmt::Formatter;
+use std::path::PathBuf;
+
+use crate::board::replay::{Action, Replay};
+use crate::board::{Board, GameResult};
+use crate::input::arguments::Parameters;
+use crate::input::plays::{Command, PlayMode};
+use crate::stats::{Record, Store};
+
+/// Something the player should be told after a command.
+#[derive(Debug)]
+pub enum Notice {
+    /// The cell of the play is not on the board
+    OutsideBoard,
+    /// The cell of the play is blocked by the mask of the board
+    Blocked,
+    /// The board isn't built yet and the command isn't a dig
+    DigFirst,
+    /// There are no plays to undo
+    NothingToUndo,
+    /// There are no plays to redo
+    NothingToRedo,
+    /// The cell can't be marked because of the flag limit
+    NoFlagsLeft,
+    /// The board was generated on no-guess mode
+    NoGuess {
+        /// if the board can be solved without guessing
+        found: bool,
+        /// boards generated
+        attempts: u32,
+    },
+    /// The game was saved to the file
+    Saved(PathBuf),
+    /// The game was replaced by the one of the file
+    Loaded(PathBuf),
+    /// The recording stopped because another game was loaded
+    RecordingStopped,
+    /// The replay was saved to the file
+    ReplaySaved(PathBuf),
+    /// The game ended and it's a practice game
+    Practice,
+    /// A file operation failed
+    Failed(String),
+}
+
+impl fmt::Display for Notice {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Notice::OutsideBoard => write!(f, "Cell outside of the board"),
+            Notice::Blocked => write!(f, "The cell is blocked, it can't be played"),
+            Notice::DigFirst => write!(f, "Dig a cell first, the first dig builds the board"),
+            Notice::NothingToUndo => write!(f, "Nothing to undo"),
+            Notice::NothingToRedo => write!(f, "Nothing to redo"),
+            Notice::NoFlagsLeft => write!(f, "No flags left"),
+            Notice::NoGuess {
+                found: true,
+                attempts,
+            } => write!(f, "No-guess board generated after {} attempts", attempts),
+            Notice::NoGuess {
+                found: false,
+                attempts,
+            } => write!(
+                f,
+                "No no-guess board found after {} attempts, guessing may be needed",
+                attempts
+            ),
+            Notice::Saved(path) => write!(f, "Game saved to {}", path.display()),
+            Notice::Loaded(path) => write!(f, "Game loaded from {}", path.display()),
+            Notice::RecordingStopped => {
+                write!(f, "Recording stopped, the loaded game is not recorded")
+            }
+            Notice::ReplaySaved(path) => write!(f, "Replay saved to {}", path.display()),
+            Notice::Practice => write!(f, "Practice game, it doesn't count toward records"),
+            Notice::Failed(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// A game being played.
+pub struct Session {
+    /// Board of the game
+    board: Board,
+    /// If the board was built, the first play builds it
+    built: bool,
+    /// If the board is generated on no-guess mode
+    no_guess: bool,
+    /// If the game was running when the session started or was loaded,
+    ///  finished games that are loaded were already counted
+    running: bool,
+    /// Plays recorded so far, if recording
+    replay: Option<Replay>,
+    /// File to write the replay to
+    record: Option<PathBuf>,
+    /// Where the finished game is counted
+    stats: Option<Store>,
+}
+
+impl Session {
+    /// Constructor
+    ///
+    /// The board is only built on the first play.
+    pub fn new(params: &Parameters) -> Session {
+        return Session {
+            board: Board::new(params),
+            built: false,
+            no_guess: params.no_guess().is_some(),
+            running: true,
+            replay: None,
+            record: None,
+            stats: None,
+        };
+    }
+
+    /// Resumes a game, ex: [loaded](Board::load) from a file.
+    pub fn resume(board: Board) -> Session {
+        return Session {
+            running: board.result() == GameResult::Continue,
+            board,
+            built: true,
+            no_guess: false,
+            replay: None,
+            record: None,
+            stats: None,
+        };
+    }
+
+    /// Records the plays to a replay file, written when the session ends.
+    ///
+    /// Only new games can be recorded.
+    pub fn with_record(mut self, path: PathBuf) -> Session {
+        if !self.built {
+            self.record = Some(path);
+        }
+        return self;
+    }
+
+    /// Counts the game on the statistics when the session ends.
+    pub fn with_stats(mut self, store: Store) -> Session {
+        self.stats = Some(store);
+        return self;
+    }
+
+    /// Board getter
+    pub fn board(&self) -> &Board {
+        return &self.board;
+    }
+
+    /// If the first play was done
+    pub fn is_built(&self) -> bool {
+        return self.built;
+    }
+
+    /// Applies a command of the player.
+    ///
+    /// [Probabilities](Command::Probabilities), [Describe](Command::Describe)
+    ///  and [Quit](Command::Quit) are up to the frontend and do nothing here.
+    /// Before the first play, only digs are accepted, the other plays,
+    ///  undo, redo, save and load are refused.
+    ///  The layers of the plays are [counted on their rows](crate::Play::unlayered)
+    ///  before applying and recording them. Plays with a row or layer
+    ///  outside of the board or on [blocked](Board::is_blocked) cells are refused.
+    ///
+    /// Returns what the player should be told.
+    pub fn apply(&mut self, command: Command) -> Vec<Notice> {
+        let mut notices = Vec::new();
+        let command = match command {
+            Command::Play(play) => match play.unlayered(self.board.rows(), self.board.layers()) {
+                Some(play)
+                    if self.board.contains(play.cell()) && self.board.is_blocked(play.cell()) =>
+                {
+                    notices.push(Notice::Blocked);
+                    return notices;
+                }
+                Some(play) if self.board.contains(play.cell()) => Command::Play(play),
+                _ => {
+                    notices.push(Notice::OutsideBoard);
+                    return notices;
+                }
+            },
+            command => command,
+        };
+        match command {
+            Command::Play(play) if !self.built && *play.mode() != PlayMode::Dig => {
+                notices.push(Notice::DigFirst);
+            }
+            Command::Play(play) if !self.built => {
+                self.board.build(play.cell());
+                self.built = true;
+                if self.no_guess {
+                    notices.push(Notice::NoGuess {
+                        found: self.board.is_guess_free(),
+                        attempts: self.board.generation_attempts(),
+                    });
+                }
+                if self.record.is_some() {
+                    self.replay = Some(Replay::record(&self.board, play.cell()));
+                }
+            }
+            Command::Undo | Command::Redo | Command::Save(_) | Command::Load(_) if !self.built => {
+                notices.push(Notice::DigFirst);
+            }
+            Command::Play(play) => {
+                let cell = play.cell();
+                match play.mode() {
+                    PlayMode::Dig => {
+                        self.board.dig(cell);
+                    }
+                    PlayMode::Chord => {
+                        self.board.chord(cell);
+                    }
+                    PlayMode::Mark => {
+                        if !self.board.mark(cell) && self.board.flags_left() == Some(0) {
+                            notices.push(Notice::NoFlagsLeft);
+                        }
+                    }
+                    PlayMode::Unmark => {
+                        self.board.unmark(cell);
+                    }
+                }
+                self.push(Action::Play(play));
+            }
+            Command::Undo => {
+                if !self.board.undo() {
+                    notices.push(Notice::NothingToUndo);
+                }
+                self.push(Action::Undo);
+            }
+            Command::Redo => {
+                if !self.board.redo() {
+                    notices.push(Notice::NothingToRedo);
+                }
+                self.push(Action::Redo);
+            }
+            Command::Save(path) => match self.board.save(&path) {
+                Ok(()) => notices.push(Notice::Saved(path)),
+                Err(e) => notices.push(Notice::Failed(format!("Can't save the game: {}", e))),
+            },
+            Command::Load(path) => match Board::load(&path) {
+                Ok(loaded) => {
+                    if self.replay.is_some() {
+                        notices.push(Notice::RecordingStopped);
+                    }
+                    notices.extend(self.end_recording());
+                    self.board = loaded;
+                    self.running = self.board.result() == GameResult::Continue;
+                    notices.push(Notice::Loaded(path));
+                }
+                Err(e) => notices.push(Notice::Failed(e.to_string())),
+            },
+            Command::Probabilities | Command::Describe(_) | Command::Quit => {}
+        }
+        return notices;
+    }
+
+    /// Auxiliary method to record an action, if recording.
+    fn push(&mut self, action: Action) {
+        if let Some(replay) = self.replay.as_mut() {
+            replay.push(&self.board, action);
+        }
+    }
+
+    /// Auxiliary method to write the replay file.
+    fn end_recording(&mut self) -> Option<Notice> {
+        let mut replay = self.replay.take()?;
+        let path = self.record.take()?;
+        replay.finish(&self.board);
+        return match replay.save(&path) {
+            Ok(()) => Some(Notice::ReplaySaved(path)),
+            Err(e) => Some(Notice::Failed(format!("Can't save the replay: {}", e))),
+        };
+    }
+
+    /// Ends the session, writing the replay and counting the game
+    ///  on the statistics if it ended.
+    ///
+    /// Returns the board, so it can be [finished](Board::finish).
+    pub fn end(mut self) -> (Board, Vec<Notice>) {
+        let mut notices = Vec::new();
+        if self.board.is_practice() {
+            notices.push(Notice::Practice);
+        }
+
+        if let (true, Some(store), Some(record)) = (
+            self.running,
+            self.stats.as_ref(),
+            Record::from_board(&self.board),
+        ) {
+            if let Err(e) = store.record(&record) {
+                notices.push(Notice::Failed(e.to_string()));
+            }
+        }
+
+        notices.extend(self.end_recording());
+        return (self.board, notices);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::CellView;
+    use crate::input::plays::Play;
+
+    #[test]
+    fn commands_before_the_first_dig_are_refused() {
+        let params = Parameters::new(9, 9, 10).unwrap().with_seed(1);
+        let mut session = Session::new(&params);
+        let path = PathBuf::from("mineswipper-never-written");
+
+        let commands = [
+            Command::Undo,
+            Command::Redo,
+            Command::Save(path.clone()),
+            Command::Load(path.clone()),
+        ];
+        for command in commands {
+            let notices = session.apply(command);
+            assert!(matches!(notices[..], [Notice::DigFirst]));
+            assert!(!session.is_built());
+        }
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn only_a_dig_builds_the_board() {
+        let params = Parameters::new(9, 9, 10).unwrap().with_seed(1);
+        let mut session = Session::new(&params);
+
+        for mode in [PlayMode::Mark, PlayMode::Unmark, PlayMode::Chord] {
+            let notices = session.apply(Command::Play(Play::new(mode, (4, 4))));
+            assert!(matches!(notices[..], [Notice::DigFirst]));
+            assert!(!session.is_built());
+            assert_eq!(session.board().cell_view((4, 4)), CellView::Hidden);
+        }
+
+        session.apply(Command::Play(Play::new(PlayMode::Dig, (4, 4))));
+        assert!(session.is_built());
+        assert_eq!(session.board().cell_view((4, 4)), CellView::Empty);
+    }
+}
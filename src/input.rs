@@ -1,5 +1,6 @@
 //! Module in charge of all user input
-//!  parsing (game plays and initial game variables).
+//!  parsing (game plays, mouse clicks and initial game variables).
 
 pub mod arguments;
+pub mod mouse;
 pub mod plays;
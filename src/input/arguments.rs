@@ -1,80 +1,1232 @@
 //! Functions to read and parse the variables of
-//!  game (columns, rows and mines).
+//!  game (columns, rows and mines) from the command line.
+//!
+//! The board can be described either by a preset:
+//!  - `--beginner`: 9x9 with 10 mines
+//!  - `--intermediate`: 16x16 with 40 mines
+//!  - `--expert`: 16x30 with 99 mines
+//!
+//! or by its dimensions, ex: `--rows 10 --columns 20 --mines 30`.
+//! The shape of the cells is chosen with `--topology`,
+//!  see [Topology], `--neighbourhood` the cells counted by the numbers,
+//!  see [Neighbourhood], `--layers` stacks several layers of cells into
+//!  a 3D board and `--wrap` joins the opposite edges of the board.
+//! `--mask` blocks some of the cells, see [Mask], the board then
+//!  takes the rows and columns of the mask.
+//! Values can also be given with `=`, ex: `--rows=10`.
+//!
+//! A saved game can be resumed with `--load <file>` instead.
+//!
+//! The game is played on a full-screen interface when the terminal
+//!  supports it, `--line` reads the plays line by line instead.
+//! `--theme` and `--colors` choose the colours of the board,
+//!  see [theme](crate::theme), and `--accessible` describes the board
+//!  in words instead, see [accessible](crate::accessible).
+//!
+//! `replay <file>` plays again a game recorded with `--record <file>`
+//!  and `verify <file>` checks that the recorded game is real.
+//!
+//! `stats` shows the statistics of the finished games.
+//!
 
-use std::io::Write;
-use std::{io, process};
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fmt::Formatter;
+use std::path::PathBuf;
+
+use crate::board::mask::{Mask, MaskError};
+use crate::board::neighbourhood::{Neighbourhood, NeighbourhoodError};
+use crate::board::topology::Topology;
+use crate::theme::Depth;
+
+/// Maximum number of cells of the empty area created around the first play.
+const MAX_OPENING: u32 = 40;
+
+/// Maximum number of cells of a board.
+///
+/// Bigger boards would take too much memory.
+pub const MAX_CELLS: u32 = 1 << 24;
+
+/// Number of boards generated on no-guess mode
+///  if `--attempts` isn't given.
+pub const DEFAULT_ATTEMPTS: u32 = 1000;
+
+/// Rows used if neither a preset nor the rows are given
+const DEFAULT_ROWS: u16 = 15;
+/// Columns used if neither a preset nor the columns are given
+const DEFAULT_COLUMNS: u16 = 30;
+/// Mines used if neither a preset nor the mines are given
+const DEFAULT_MINES: u32 = 50;
+/// With a mask and without the mines given, there is
+///  a mine for each this number of cells
+const MASK_CELLS_PER_MINE: u32 = 8;
+
+/// Help message of the command line
+pub const USAGE: &str = "\
+usage: mineswipper [options]
+       mineswipper replay <file> [--speed <n>] [--paused]
+       mineswipper verify <file>
+       mineswipper stats
+
+options:
+  --beginner            9x9 board with 10 mines
+  --intermediate        16x16 board with 40 mines
+  --expert              16x30 board with 99 mines
+  --rows <n>            number of rows of a custom board
+  --columns <n>         number of columns of a custom board
+  --mines <n>           number of mines of a custom board
+  --topology <name>     shape of the cells: square (default, 8 neighbours)
+                        or hexagonal (6 neighbours)
+  --neighbourhood <name|file>
+                        cells counted by the numbers: king (default), knight,
+                        cross, radius-2 or a neighbourhood file (square boards only)
+  --layers <n>          number of layers of a 3D board, each cell also has
+                        the ones on the layers above and below as neighbours
+  --mask <name|file>    cells that can't be played: heart, ring or a mask file,
+                        the board takes the rows and columns of the mask
+  --wrap                join the opposite edges, so every cell has all its neighbours
+  --seed <n>            seed used to generate the board
+  --no-guess            generate a board that can be solved without guessing
+  --attempts <n>        boards to try on no-guess mode (default 1000)
+  --flag-limit <n>      maximum number of cells marked at the same time
+  --practice            allow undo after the game ends (not counted for records)
+  --load <file>         resume a saved game (can't be combined with other options)
+  --record <file>       record the plays of the game to a replay file
+  --line                read the plays line by line instead of the full-screen interface
+  --theme <name|file>   colours of the board: classic, okabe-ito, tol-bright
+                        (both colour-blind safe), plain or a theme file
+  --colors <mode>       auto, truecolor, 16 or none (default auto: none if NO_COLOR
+                        is set or the output isn't a terminal)
+  --accessible          describe the plays in words for screen readers (implies --line)
+
+replay options:
+  --speed <n>           playback speed, 2 is twice as fast (default 1)
+  --paused              start paused, enter steps one play and p resumes
+  -h, --help            show this message";
+
+/// Well known board configurations
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Preset {
+    /// 9x9 with 10 mines
+    Beginner,
+    /// 16x16 with 40 mines
+    Intermediate,
+    /// 16x30 with 99 mines
+    Expert,
+}
+
+impl Preset {
+    /// Rows, columns and mines of the preset
+    fn values(&self) -> (u16, u16, u32) {
+        return match self {
+            Preset::Beginner => (9, 9, 10),
+            Preset::Intermediate => (16, 16, 40),
+            Preset::Expert => (16, 30, 99),
+        };
+    }
+
+    /// Preset with the given rows, columns and mines, if there is one
+    pub fn matching(rows: u16, columns: u16, mines: u32) -> Option<Preset> {
+        return [Preset::Beginner, Preset::Intermediate, Preset::Expert]
+            .iter()
+            .copied()
+            .find(|preset| preset.values() == (rows, columns, mines));
+    }
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Preset::Beginner => write!(f, "beginner"),
+            Preset::Intermediate => write!(f, "intermediate"),
+            Preset::Expert => write!(f, "expert"),
+        }
+    }
+}
+
+/// Returned when the game variables can't be used to build a board
+#[derive(Debug)]
+pub enum InvalidParameters {
+    /// The board must have at least one row
+    NoRows,
+    /// The board must have at least one column
+    NoColumns,
+    /// The board must have at least one layer
+    NoLayers,
+    /// The board has more than [MAX_CELLS] cells
+    TooManyCells {
+        /// rows requested
+        rows: u16,
+        /// columns requested
+        columns: u16,
+    },
+    /// The layers have more than [MAX_CELLS] cells or rows
+    TooManyLayers {
+        /// layers requested
+        layers: u16,
+    },
+    /// There isn't enough room for the mines
+    ///  outside of the empty area of the first play
+    TooManyMines {
+        /// mines requested
+        mines: u32,
+        /// maximum number of mines on that board
+        max: u32,
+    },
+    /// The board is too small for its edges to wrap
+    TooSmallToWrap {
+        /// minimum number of rows and columns
+        minimum: u16,
+    },
+    /// Only square boards can have a custom neighbourhood
+    NeighbourhoodNotSquare,
+    /// The mask doesn't have the rows and columns of the board
+    MaskSize {
+        /// rows of the mask
+        rows: u16,
+        /// columns of the mask
+        columns: u16,
+    },
+    /// Hexagonal boards need an even number of rows to wrap,
+    ///  otherwise the first and last rows are both not shifted
+    OddRowsToWrap,
+}
+
+impl Error for InvalidParameters {}
+
+impl fmt::Display for InvalidParameters {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            InvalidParameters::NoRows => write!(f, "The board must have at least one row"),
+            InvalidParameters::NoColumns => write!(f, "The board must have at least one column"),
+            InvalidParameters::NoLayers => write!(f, "The board must have at least one layer"),
+            InvalidParameters::TooManyCells { rows, columns } => write!(
+                f,
+                "A board of {}x{} is too big, boards can have at most {} cells",
+                rows, columns, MAX_CELLS
+            ),
+            InvalidParameters::TooManyLayers { layers } => write!(
+                f,
+                "{} layers are too many, boards can have at most {} cells and {} rows \
+                 on all their layers",
+                layers,
+                MAX_CELLS,
+                u16::MAX
+            ),
+            InvalidParameters::TooManyMines { mines, max } => write!(
+                f,
+                "Too many mines ({}), this board only has room for {} mines \
+                 outside of the safe area of the first play",
+                mines, max
+            ),
+            InvalidParameters::TooSmallToWrap { minimum } => write!(
+                f,
+                "This board needs at least {0} rows and {0} columns for its edges to wrap",
+                minimum
+            ),
+            InvalidParameters::NeighbourhoodNotSquare => {
+                write!(f, "Only square boards can have a custom neighbourhood")
+            }
+            InvalidParameters::MaskSize { rows, columns } => write!(
+                f,
+                "The mask is {}x{}, it must have the rows and columns of the board",
+                rows, columns
+            ),
+            InvalidParameters::OddRowsToWrap => write!(
+                f,
+                "Hexagonal boards need an even number of rows for their edges to wrap"
+            ),
+        }
+    }
+}
 
 /// To store the parameters read from the user input
+#[derive(PartialEq, Eq, Clone, Debug)]
 pub struct Parameters {
     /// number of rows of the board
-    rows: u8,
+    rows: u16,
     /// number of columns of the board
-    columns: u8,
+    columns: u16,
+    /// number of layers of the board, 1 on flat boards
+    layers: u16,
     /// number of mines on the board
-    mines: u8,
+    mines: u32,
+    /// shape of the cells of the board
+    topology: Topology,
+    /// cells counted by the numbers, `None` for the ones
+    ///  of the [topology](Topology::neighbour_offsets)
+    neighbourhood: Option<Neighbourhood>,
+    /// cells that can't be played, `None` if all can
+    mask: Option<Mask>,
+    /// if the opposite edges of the board are next to each other
+    wrap: bool,
+    /// seed used to generate the board.
+    ///
+    /// If `None` a random one is used.
+    seed: Option<u64>,
+    /// preset used to create this parameters, if any
+    preset: Option<Preset>,
+    /// If set the board must be solvable without guessing,
+    ///  and this is the maximum number of boards to generate.
+    no_guess: Option<u32>,
+    /// maximum number of cells marked at the same time.
+    ///
+    /// If `None` there is no limit.
+    flag_limit: Option<u32>,
+    /// If undo can be used after the game ends
+    practice: bool,
 }
 
 /// Constructor + Getters
 impl Parameters {
     /// Constructor
-    pub fn new(rows: u8, columns: u8, mines: u8) -> Parameters {
+    ///
+    /// Fails if the board has no cells, more than [MAX_CELLS] cells
+    ///  or there isn't room for all the mines outside
+    ///  the [opening](Parameters::opening).
+    pub fn new(rows: u16, columns: u16, mines: u32) -> Result<Parameters, InvalidParameters> {
+        return Parameters::layered(rows, columns, 1, mines);
+    }
+
+    /// Constructor of a board with several layers,
+    ///  see [with_layers](Parameters::with_layers).
+    ///
+    /// Fails as [new](Parameters::new), counting the cells of all the layers.
+    pub fn layered(
+        rows: u16,
+        columns: u16,
+        layers: u16,
+        mines: u32,
+    ) -> Result<Parameters, InvalidParameters> {
+        if rows == 0 {
+            return Err(InvalidParameters::NoRows);
+        }
+        if columns == 0 {
+            return Err(InvalidParameters::NoColumns);
+        }
+        match (rows as u32).checked_mul(columns as u32) {
+            Some(cells) if cells <= MAX_CELLS => {}
+            _ => return Err(InvalidParameters::TooManyCells { rows, columns }),
+        }
+
+        let params = Parameters {
+            rows,
+            columns,
+            layers,
+            mines,
+            topology: Topology::Square,
+            neighbourhood: None,
+            mask: None,
+            wrap: false,
+            seed: None,
+            preset: None,
+            no_guess: None,
+            flag_limit: None,
+            practice: false,
+        };
+
+        return params.validate();
+    }
+
+    /// Constructor from a [Preset]
+    pub fn from_preset(preset: Preset) -> Parameters {
+        let (rows, columns, mines) = preset.values();
         return Parameters {
             rows,
             columns,
+            layers: 1,
             mines,
+            topology: Topology::Square,
+            neighbourhood: None,
+            mask: None,
+            wrap: false,
+            seed: None,
+            preset: Some(preset),
+            no_guess: None,
+            flag_limit: None,
+            practice: false,
+        };
+    }
+
+    /// Set the shape of the cells of the board
+    ///
+    /// Fails as [validate](Parameters::validate), ex: if the board
+    ///  has a custom neighbourhood or can't wrap with that shape.
+    pub fn with_topology(mut self, topology: Topology) -> Result<Parameters, InvalidParameters> {
+        self.topology = topology;
+        return self.validate();
+    }
+
+    /// Count the mines of other cells on the numbers.
+    ///
+    /// Fails as [validate](Parameters::validate), ex: if the board isn't
+    ///  [square](Topology::Square), or if the bigger
+    ///  [opening](Parameters::opening) leaves no room for the mines.
+    pub fn with_neighbourhood(
+        mut self,
+        neighbourhood: Neighbourhood,
+    ) -> Result<Parameters, InvalidParameters> {
+        self.neighbourhood = match neighbourhood.name() {
+            // the one of square boards
+            Some("king") => None,
+            _ => Some(neighbourhood),
         };
+        return self.validate();
+    }
+
+    /// Stack several layers of cells, each with the rows and columns
+    ///  of the board, so the cells also have as neighbours the ones
+    ///  on the layers above and below.
+    ///
+    /// Fails as [validate](Parameters::validate), ex: if there are no
+    ///  layers, if the board gets too big, or if there isn't room for the mines.
+    pub fn with_layers(mut self, layers: u16) -> Result<Parameters, InvalidParameters> {
+        self.layers = layers;
+        return self.validate();
+    }
+
+    /// Block the cells of the mask, so they can't be played.
+    ///
+    /// Fails as [validate](Parameters::validate), ex: if the mask doesn't
+    ///  have the rows and columns of the board, or if there isn't room
+    ///  for the mines on the cells left.
+    pub fn with_mask(mut self, mask: Mask) -> Result<Parameters, InvalidParameters> {
+        self.mask = Some(mask);
+        return self.validate();
+    }
+
+    /// Join the opposite edges of the board, so the cells of the
+    ///  first and last rows (and columns) are neighbours.
+    ///  The first and last layers are never neighbours.
+    ///
+    /// Fails as [validate](Parameters::validate), ex: if the board
+    ///  is too small for the neighbourhood.
+    pub fn with_wrap(mut self) -> Result<Parameters, InvalidParameters> {
+        self.wrap = true;
+        return self.validate();
+    }
+
+    /// Checks that the variables of the board can be played together,
+    ///  so the setters can be called in any order.
+    ///
+    /// Fails if:
+    ///  - there are no layers, or the layers have more than [MAX_CELLS]
+    ///    cells or [u16::MAX] rows (the board dimensions are checked
+    ///    on [construction](Parameters::layered)).
+    ///  - the mask doesn't have the rows and columns of the board.
+    ///  - the board has a custom neighbourhood and isn't
+    ///    [square](Topology::Square).
+    ///  - the board wraps and is too small for the neighbourhood (less
+    ///    than 3 rows or columns for the default one), since the same
+    ///    cell would be a neighbour more than once, or it's a
+    ///    [hexagonal](Topology::Hexagonal) board with an odd number of rows.
+    ///  - there isn't room for the mines outside the [opening](Parameters::opening).
+    fn validate(self) -> Result<Parameters, InvalidParameters> {
+        let layers = self.layers;
+        if layers == 0 {
+            return Err(InvalidParameters::NoLayers);
+        }
+        // the rows of all the layers are numbered one after another
+        let rows = self.rows as u32 * layers as u32;
+        if rows > u16::MAX as u32 || rows * self.columns as u32 > MAX_CELLS {
+            return Err(InvalidParameters::TooManyLayers { layers });
+        }
+
+        if let Some(mask) = &self.mask {
+            if (mask.rows(), mask.columns()) != (self.rows, self.columns) {
+                return Err(InvalidParameters::MaskSize {
+                    rows: mask.rows(),
+                    columns: mask.columns(),
+                });
+            }
+        }
+
+        if self.neighbourhood.is_some() && self.topology != Topology::Square {
+            return Err(InvalidParameters::NeighbourhoodNotSquare);
+        }
+
+        if self.wrap {
+            let reach = self.neighbourhood.as_ref().map_or(1, Neighbourhood::reach);
+            let minimum = 2 * reach as u16 + 1;
+            if self.rows < minimum || self.columns < minimum {
+                return Err(InvalidParameters::TooSmallToWrap { minimum });
+            }
+            if self.topology == Topology::Hexagonal && self.rows % 2 == 1 {
+                return Err(InvalidParameters::OddRowsToWrap);
+            }
+        }
+
+        let max = self.cells() - self.opening();
+        if self.mines > max {
+            return Err(InvalidParameters::TooManyMines {
+                mines: self.mines,
+                max,
+            });
+        }
+        return Ok(self);
+    }
+
+    /// Set the seed used to generate the board,
+    ///  so the same board can be generated again.
+    pub fn with_seed(mut self, seed: u64) -> Parameters {
+        self.seed = Some(seed);
+        return self;
+    }
+
+    /// Generate a board that can be solved without guessing,
+    ///  trying at most `attempts` boards.
+    pub fn with_no_guess(mut self, attempts: u32) -> Parameters {
+        self.no_guess = Some(attempts);
+        return self;
+    }
+
+    /// Limit the number of cells marked at the same time
+    pub fn with_flag_limit(mut self, limit: u32) -> Parameters {
+        self.flag_limit = Some(limit);
+        return self;
+    }
+
+    /// Allow undo after the game ends
+    pub fn with_practice(mut self) -> Parameters {
+        self.practice = true;
+        return self;
     }
 
     /// rows getter
-    pub fn rows(&self) -> u8 {
+    pub fn rows(&self) -> u16 {
         return self.rows;
     }
 
     /// columns getter
-    pub fn columns(&self) -> u8 {
+    pub fn columns(&self) -> u16 {
         return self.columns;
     }
 
+    /// layers getter
+    pub fn layers(&self) -> u16 {
+        return self.layers;
+    }
+
     /// mines getter
-    pub fn mines(&self) -> u8 {
+    pub fn mines(&self) -> u32 {
         return self.mines;
     }
+
+    /// topology getter
+    pub fn topology(&self) -> Topology {
+        return self.topology;
+    }
+
+    /// neighbourhood getter
+    ///
+    /// `None` if the numbers count the neighbours of the
+    ///  [topology](Topology::neighbour_offsets).
+    pub fn neighbourhood(&self) -> Option<&Neighbourhood> {
+        return self.neighbourhood.as_ref();
+    }
+
+    /// mask getter
+    ///
+    /// `None` if all the cells can be played.
+    pub fn mask(&self) -> Option<&Mask> {
+        return self.mask.as_ref();
+    }
+
+    /// wrap getter
+    pub fn wrap(&self) -> bool {
+        return self.wrap;
+    }
+
+    /// seed getter
+    pub fn seed(&self) -> Option<u64> {
+        return self.seed;
+    }
+
+    /// no-guess attempts getter
+    pub fn no_guess(&self) -> Option<u32> {
+        return self.no_guess;
+    }
+
+    /// flag limit getter
+    pub fn flag_limit(&self) -> Option<u32> {
+        return self.flag_limit;
+    }
+
+    /// practice getter
+    pub fn practice(&self) -> bool {
+        return self.practice;
+    }
+
+    /// preset getter
+    pub fn preset(&self) -> Option<Preset> {
+        return self.preset;
+    }
+
+    /// Total number of cells of the board that can be played, on all its layers
+    ///
+    /// Can't overflow since the dimensions are checked on construction.
+    pub fn cells(&self) -> u32 {
+        let layer = match &self.mask {
+            Some(mask) => mask.playable(),
+            None => self.rows as u32 * self.columns as u32,
+        };
+        return layer * self.layers as u32;
+    }
+
+    /// Number of cells of the empty area created around the first play.
+    ///
+    /// Smaller boards get a smaller area, so there is still room for mines.
+    ///  With a custom [neighbourhood](Parameters::neighbourhood) the area
+    ///  has room for the whole neighbourhood of the first play, which on
+    ///  boards with layers also takes the layers above and below.
+    pub fn opening(&self) -> u32 {
+        let opening = (self.cells() / 4).clamp(1, MAX_OPENING);
+        // the layer of the first play and the ones next to it
+        let layers = self.layers.min(3) as u32;
+        return match &self.neighbourhood {
+            Some(neighbourhood) => opening
+                .max((neighbourhood.offsets().len() as u32 + 1) * layers)
+                .min(self.cells()),
+            None => opening,
+        };
+    }
 }
 
-/// Requests the user game variables (rows, columns, mines) from the user
-///
-/// TODO add difficulty option that have defined rows, column and mines values
-pub fn read_parameters() -> Parameters {
-    let mut input = String::new();
-    print!("rows: ");
-    io::stdout().flush();
-    io::stdin().read_line(&mut input);
-    let rows = input.trim().parse::<u8>().unwrap_or_else(|_| {
-        eprintln!("Invalid rows number!");
-        process::exit(1);
-    });
-
-    print!("columns: ");
-    io::stdout().flush();
-    input.clear();
-    io::stdin().read_line(&mut input);
-    let columns = input.trim().parse::<u8>().unwrap_or_else(|_| {
-        eprintln!("Invalid columns number!");
-        process::exit(1);
-    });
-
-    print!("mines: ");
-    io::stdout().flush();
-    input.clear();
-    io::stdin().read_line(&mut input);
-    let mines = input.trim().parse::<u8>().unwrap_or_else(|_| {
-        eprintln!("Invalid mines number!");
-        process::exit(1);
-    });
-
-    return Parameters {
-        rows,
-        columns,
-        mines,
+/// Returned when the command line arguments are invalid
+#[derive(Debug)]
+pub enum InvalidArgument {
+    /// The argument is not known
+    Unknown(String),
+    /// The argument requires a value
+    MissingValue(String),
+    /// The value of the argument can't be parsed
+    InvalidValue {
+        /// argument name
+        argument: String,
+        /// value given
+        value: String,
+    },
+    /// The same argument was given more than once
+    Repeated(String),
+    /// More than one preset was given
+    MultiplePresets,
+    /// A preset was given together with a board dimension
+    PresetWithDimensions,
+    /// `--attempts` was given without `--no-guess`
+    AttemptsWithoutNoGuess,
+    /// `--load` was given together with other options
+    LoadWithOptions,
+    /// A mask was given together with a preset, the rows or the columns
+    MaskWithDimensions,
+    /// The neighbourhood isn't a built-in one nor a valid file
+    Neighbourhood(NeighbourhoodError),
+    /// The mask isn't a built-in one nor a valid file
+    Mask(MaskError),
+    /// The resulting game variables are invalid
+    Parameters(InvalidParameters),
+}
+
+impl Error for InvalidArgument {}
+
+impl fmt::Display for InvalidArgument {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            InvalidArgument::Unknown(arg) => write!(f, "Unknown argument {}", arg),
+            InvalidArgument::MissingValue(arg) => write!(f, "Missing value for {}", arg),
+            InvalidArgument::InvalidValue { argument, value } => {
+                write!(f, "Invalid value {:?} for {}", value, argument)
+            }
+            InvalidArgument::Repeated(arg) => write!(f, "{} given more than once", arg),
+            InvalidArgument::MultiplePresets => write!(f, "Only one preset can be given"),
+            InvalidArgument::PresetWithDimensions => write!(
+                f,
+                "A preset can't be combined with --rows, --columns or --mines"
+            ),
+            InvalidArgument::AttemptsWithoutNoGuess => {
+                write!(f, "--attempts can only be used with --no-guess")
+            }
+            InvalidArgument::LoadWithOptions => {
+                write!(f, "--load can't be combined with other options")
+            }
+            InvalidArgument::MaskWithDimensions => write!(
+                f,
+                "A mask can't be combined with a preset, --rows or --columns"
+            ),
+            InvalidArgument::Neighbourhood(e) => e.fmt(f),
+            InvalidArgument::Mask(e) => e.fmt(f),
+            InvalidArgument::Parameters(e) => e.fmt(f),
+        }
+    }
+}
+
+impl From<InvalidParameters> for InvalidArgument {
+    fn from(e: InvalidParameters) -> InvalidArgument {
+        return InvalidArgument::Parameters(e);
+    }
+}
+
+/// How the game is played, independent of the board
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct Options {
+    /// File to record the plays of the game to
+    record: Option<PathBuf>,
+    /// If the plays are read line by line instead of
+    ///  using the full-screen interface
+    line: bool,
+    /// Name of a built-in theme or path of a theme file
+    theme: Option<String>,
+    /// Colours supported by the terminal, `None` to detect them
+    colors: Option<Depth>,
+    /// If the board is described in words instead of printed,
+    ///  only on line mode
+    accessible: bool,
+}
+
+/// Just getters
+impl Options {
+    /// Record getter
+    pub fn record(&self) -> Option<&PathBuf> {
+        return self.record.as_ref();
+    }
+
+    /// Line mode getter
+    pub fn line(&self) -> bool {
+        return self.line;
+    }
+
+    /// Theme getter
+    pub fn theme(&self) -> Option<&str> {
+        return self.theme.as_deref();
+    }
+
+    /// Colors getter
+    pub fn colors(&self) -> Option<Depth> {
+        return self.colors;
+    }
+
+    /// Accessible mode getter
+    pub fn accessible(&self) -> bool {
+        return self.accessible;
+    }
+}
+
+/// What the user asked for on the command line
+pub enum Arguments {
+    /// Play a game with the given parameters
+    Play(Parameters, Options),
+    /// Resume the game saved on the given file
+    Load(PathBuf, Options),
+    /// Play again a recorded game
+    Replay {
+        /// replay file
+        path: PathBuf,
+        /// playback speed, 1 is real time
+        speed: f64,
+        /// if the playback starts paused
+        paused: bool,
+    },
+    /// Check that a recorded game is real
+    Verify(PathBuf),
+    /// Show the statistics of the finished games
+    Stats,
+    /// Show the [usage](USAGE) message
+    Help,
+}
+
+/// Auxiliary function to parse the value of an argument
+fn parse_value<T: std::str::FromStr>(argument: &str, value: &str) -> Result<T, InvalidArgument> {
+    return value
+        .parse::<T>()
+        .map_err(|_| InvalidArgument::InvalidValue {
+            argument: argument.to_string(),
+            value: value.to_string(),
+        });
+}
+
+/// Auxiliary function to store the value of an argument,
+///  failing if it was already given.
+fn set_once<T>(slot: &mut Option<T>, argument: &str, value: T) -> Result<(), InvalidArgument> {
+    if slot.is_some() {
+        return Err(InvalidArgument::Repeated(argument.to_string()));
+    }
+    *slot = Some(value);
+    return Ok(());
+}
+
+/// Parses the command line arguments (without the program name).
+pub fn parse_arguments<I: IntoIterator<Item = String>>(
+    args: I,
+) -> Result<Arguments, InvalidArgument> {
+    let mut preset = None;
+    let mut rows = None;
+    let mut columns = None;
+    let mut layers = None;
+    let mut mines = None;
+    let mut seed = None;
+    let mut topology = None;
+    let mut neighbourhood = None;
+    let mut mask = None;
+    let mut no_guess = false;
+    let mut practice = false;
+    let mut wrap = false;
+    let mut attempts = None;
+    let mut flag_limit = None;
+    let mut load = None;
+    let mut record = None;
+    let mut frontend = Options::default();
+    // `--colors auto` is the same as not giving it, but can't be repeated either
+    let mut colors_given = None;
+    // options other than the ones about how the game is played
+    let mut options = 0;
+
+    let mut args = args.into_iter().peekable();
+    match args.peek().map(String::as_str) {
+        Some("replay") => {
+            args.next();
+            return parse_replay_arguments(args);
+        }
+        Some("stats") => {
+            args.next();
+            return match args.next() {
+                Some(arg) if arg == "-h" || arg == "--help" => Ok(Arguments::Help),
+                Some(arg) => Err(InvalidArgument::Unknown(arg)),
+                None => Ok(Arguments::Stats),
+            };
+        }
+        Some("verify") => {
+            args.next();
+            return match (args.next(), args.next()) {
+                (Some(arg), _) if arg == "-h" || arg == "--help" => Ok(Arguments::Help),
+                (Some(path), None) => Ok(Arguments::Verify(PathBuf::from(path))),
+                (None, _) => Err(InvalidArgument::MissingValue("verify".to_string())),
+                (_, Some(arg)) => Err(InvalidArgument::Unknown(arg)),
+            };
+        }
+        _ => {}
+    }
+
+    while let Some(arg) = args.next() {
+        // split `--name=value` arguments
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) => (name.to_string(), Some(value.to_string())),
+            None => (arg.clone(), None),
+        };
+        if !matches!(
+            name.as_str(),
+            "--line" | "--theme" | "--colors" | "--accessible"
+        ) {
+            options += 1;
+        }
+
+        match name.as_str() {
+            "-h" | "--help" => return Ok(Arguments::Help),
+            "--beginner" | "--intermediate" | "--expert" => {
+                if inline_value.is_some() {
+                    return Err(InvalidArgument::Unknown(arg));
+                }
+                let new_preset = match name.as_str() {
+                    "--beginner" => Preset::Beginner,
+                    "--intermediate" => Preset::Intermediate,
+                    _ => Preset::Expert,
+                };
+                if preset.is_some() {
+                    return Err(InvalidArgument::MultiplePresets);
+                }
+                preset = Some(new_preset);
+            }
+            "--no-guess" | "--practice" | "--wrap" => {
+                if inline_value.is_some() {
+                    return Err(InvalidArgument::Unknown(arg));
+                }
+                let flag = match name.as_str() {
+                    "--no-guess" => &mut no_guess,
+                    "--wrap" => &mut wrap,
+                    _ => &mut practice,
+                };
+                if *flag {
+                    return Err(InvalidArgument::Repeated(name));
+                }
+                *flag = true;
+            }
+            "--line" | "--accessible" if inline_value.is_none() => {
+                let flag = match name.as_str() {
+                    "--line" => &mut frontend.line,
+                    _ => &mut frontend.accessible,
+                };
+                if *flag {
+                    return Err(InvalidArgument::Repeated(name));
+                }
+                *flag = true;
+            }
+            "--theme" => {
+                let value = match inline_value.or_else(|| args.next()) {
+                    Some(value) => value,
+                    None => return Err(InvalidArgument::MissingValue(name)),
+                };
+                set_once(&mut frontend.theme, &name, value)?;
+            }
+            "--colors" => {
+                let value = match inline_value.or_else(|| args.next()) {
+                    Some(value) => value,
+                    None => return Err(InvalidArgument::MissingValue(name)),
+                };
+                let colors = match value.as_str() {
+                    "auto" => None,
+                    "truecolor" => Some(Depth::TrueColor),
+                    "16" => Some(Depth::Basic),
+                    "none" => Some(Depth::Plain),
+                    _ => {
+                        return Err(InvalidArgument::InvalidValue {
+                            argument: name,
+                            value,
+                        })
+                    }
+                };
+                set_once(&mut colors_given, &name, colors)?;
+            }
+            "--topology" => {
+                let value = match inline_value.or_else(|| args.next()) {
+                    Some(value) => value,
+                    None => return Err(InvalidArgument::MissingValue(name)),
+                };
+                set_once(&mut topology, &name, parse_value(&name, &value)?)?;
+            }
+            "--neighbourhood" | "--neighborhood" => {
+                let value = match inline_value.or_else(|| args.next()) {
+                    Some(value) => value,
+                    None => return Err(InvalidArgument::MissingValue(name)),
+                };
+                let found = Neighbourhood::find(&value).map_err(InvalidArgument::Neighbourhood)?;
+                set_once(&mut neighbourhood, "--neighbourhood", found)?;
+            }
+            "--mask" => {
+                let value = match inline_value.or_else(|| args.next()) {
+                    Some(value) => value,
+                    None => return Err(InvalidArgument::MissingValue(name)),
+                };
+                set_once(
+                    &mut mask,
+                    &name,
+                    Mask::find(&value).map_err(InvalidArgument::Mask)?,
+                )?;
+            }
+            "--load" | "--record" => {
+                let value = match inline_value.or_else(|| args.next()) {
+                    Some(value) => value,
+                    None => return Err(InvalidArgument::MissingValue(name)),
+                };
+                match name.as_str() {
+                    "--load" => set_once(&mut load, &name, PathBuf::from(value))?,
+                    _ => set_once(&mut record, &name, PathBuf::from(value))?,
+                }
+            }
+            "--rows" | "--columns" | "--layers" | "--mines" | "--seed" | "--attempts"
+            | "--flag-limit" => {
+                let value = match inline_value.or_else(|| args.next()) {
+                    Some(value) => value,
+                    None => return Err(InvalidArgument::MissingValue(name)),
+                };
+                match name.as_str() {
+                    "--rows" => set_once(&mut rows, &name, parse_value(&name, &value)?)?,
+                    "--columns" => set_once(&mut columns, &name, parse_value(&name, &value)?)?,
+                    "--layers" => set_once(&mut layers, &name, parse_value(&name, &value)?)?,
+                    "--mines" => set_once(&mut mines, &name, parse_value(&name, &value)?)?,
+                    "--flag-limit" => {
+                        set_once(&mut flag_limit, &name, parse_value(&name, &value)?)?
+                    }
+                    "--attempts" => match parse_value(&name, &value)? {
+                        0 => {
+                            return Err(InvalidArgument::InvalidValue {
+                                argument: name,
+                                value,
+                            })
+                        }
+                        n => set_once(&mut attempts, &name, n)?,
+                    },
+                    _ => set_once(&mut seed, &name, parse_value(&name, &value)?)?,
+                }
+            }
+            _ => return Err(InvalidArgument::Unknown(arg)),
+        }
+    }
+
+    match load {
+        Some(_) if options > 1 => return Err(InvalidArgument::LoadWithOptions),
+        Some(path) => {
+            frontend.colors = colors_given.flatten();
+            return Ok(Arguments::Load(path, frontend));
+        }
+        None => {}
+    }
+
+    if mask.is_some() && (preset.is_some() || rows.is_some() || columns.is_some()) {
+        return Err(InvalidArgument::MaskWithDimensions);
+    }
+
+    let params = match (preset, &mask) {
+        (Some(_), _) if rows.is_some() || columns.is_some() || mines.is_some() => {
+            return Err(InvalidArgument::PresetWithDimensions)
+        }
+        (Some(preset), _) => Parameters::from_preset(preset),
+        (None, Some(mask)) => {
+            let cells = mask.playable().saturating_mul(layers.unwrap_or(1) as u32);
+            Parameters::layered(
+                mask.rows(),
+                mask.columns(),
+                layers.unwrap_or(1),
+                mines.unwrap_or(cells / MASK_CELLS_PER_MINE),
+            )?
+        }
+        (None, None) => Parameters::layered(
+            rows.unwrap_or(DEFAULT_ROWS),
+            columns.unwrap_or(DEFAULT_COLUMNS),
+            layers.unwrap_or(1),
+            mines.unwrap_or(DEFAULT_MINES),
+        )?,
     };
+
+    let params = match topology {
+        Some(topology) => params.with_topology(topology)?,
+        None => params,
+    };
+
+    let params = match neighbourhood {
+        Some(neighbourhood) => params.with_neighbourhood(neighbourhood)?,
+        None => params,
+    };
+
+    let params = match (preset, layers) {
+        (Some(_), Some(layers)) => params.with_layers(layers)?,
+        _ => params,
+    };
+
+    let params = match mask {
+        Some(mask) => params.with_mask(mask)?,
+        None => params,
+    };
+
+    let params = match wrap {
+        true => params.with_wrap()?,
+        false => params,
+    };
+
+    let params = match seed {
+        Some(seed) => params.with_seed(seed),
+        None => params,
+    };
+
+    let params = match (no_guess, attempts) {
+        (false, Some(_)) => return Err(InvalidArgument::AttemptsWithoutNoGuess),
+        (false, None) => params,
+        (true, attempts) => params.with_no_guess(attempts.unwrap_or(DEFAULT_ATTEMPTS)),
+    };
+
+    let params = match flag_limit {
+        Some(limit) => params.with_flag_limit(limit),
+        None => params,
+    };
+
+    let params = match practice {
+        true => params.with_practice(),
+        false => params,
+    };
+
+    frontend.record = record;
+    frontend.colors = colors_given.flatten();
+    return Ok(Arguments::Play(params, frontend));
+}
+
+/// Auxiliary function of [parse_arguments] for the arguments after `replay`.
+fn parse_replay_arguments<I: Iterator<Item = String>>(
+    mut args: I,
+) -> Result<Arguments, InvalidArgument> {
+    let mut path = None;
+    let mut speed = None;
+    let mut paused = false;
+
+    while let Some(arg) = args.next() {
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) => (name.to_string(), Some(value.to_string())),
+            None => (arg.clone(), None),
+        };
+
+        match name.as_str() {
+            "-h" | "--help" => return Ok(Arguments::Help),
+            "--paused" if inline_value.is_none() => {
+                if paused {
+                    return Err(InvalidArgument::Repeated(name));
+                }
+                paused = true;
+            }
+            "--speed" => {
+                let value = match inline_value.or_else(|| args.next()) {
+                    Some(value) => value,
+                    None => return Err(InvalidArgument::MissingValue(name)),
+                };
+                match parse_value::<f64>(&name, &value)? {
+                    n if n.is_finite() && n > 0.0 => set_once(&mut speed, &name, n)?,
+                    _ => {
+                        return Err(InvalidArgument::InvalidValue {
+                            argument: name,
+                            value,
+                        })
+                    }
+                }
+            }
+            _ if !arg.starts_with('-') && path.is_none() => path = Some(PathBuf::from(arg)),
+            _ => return Err(InvalidArgument::Unknown(arg)),
+        }
+    }
+
+    return match path {
+        Some(path) => Ok(Arguments::Replay {
+            path,
+            speed: speed.unwrap_or(1.0),
+            paused,
+        }),
+        None => Err(InvalidArgument::MissingValue("replay".to_string())),
+    };
+}
+
+/// Reads the game variables from the command line arguments
+pub fn read_arguments() -> Result<Arguments, InvalidArgument> {
+    return parse_arguments(env::args().skip(1));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Auxiliary function to get the message of the error of some arguments.
+    fn error(args: &str) -> String {
+        return match parse_arguments(args.split_whitespace().map(String::from)) {
+            Ok(_) => panic!("{:?} was accepted", args),
+            Err(e) => e.to_string(),
+        };
+    }
+
+    #[test]
+    fn invalid_parameters_are_explained() {
+        let cases = [
+            ("--rows 0", "The board must have at least one row"),
+            ("--columns 0", "The board must have at least one column"),
+            ("--layers 0", "The board must have at least one layer"),
+            (
+                "--rows 65535 --columns 65535",
+                "A board of 65535x65535 is too big, boards can have at most 16777216 cells",
+            ),
+            (
+                "--rows 1000 --columns 1000 --layers 100",
+                "100 layers are too many, boards can have at most 16777216 cells \
+                 and 65535 rows on all their layers",
+            ),
+            (
+                "--rows 9 --columns 9 --mines 80",
+                "Too many mines (80), this board only has room for 61 mines \
+                 outside of the safe area of the first play",
+            ),
+            (
+                "--rows 2 --columns 9 --mines 1 --wrap",
+                "This board needs at least 3 rows and 3 columns for its edges to wrap",
+            ),
+            (
+                "--topology hexagonal --neighbourhood knight",
+                "Only square boards can have a custom neighbourhood",
+            ),
+            (
+                "--topology hexagonal --rows 9 --columns 9 --mines 10 --wrap",
+                "Hexagonal boards need an even number of rows for their edges to wrap",
+            ),
+        ];
+        for (args, message) in cases {
+            assert_eq!(error(args), message, "{}", args);
+        }
+
+        // the mask takes the size of the board on the command line
+        let mask = Mask::preset("heart").unwrap();
+        let e = Parameters::new(9, 9, 10)
+            .unwrap()
+            .with_mask(mask)
+            .unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "The mask is 11x13, it must have the rows and columns of the board"
+        );
+    }
+
+    #[test]
+    fn parameters_are_checked_in_any_order() {
+        let radius = Neighbourhood::preset("radius-2").unwrap();
+        // radius-2 reaches 2 cells away, so wrapping needs 5 rows and columns
+        let wrapped = Parameters::new(4, 4, 0).unwrap().with_wrap().unwrap();
+        assert!(matches!(
+            wrapped.with_neighbourhood(radius.clone()),
+            Err(InvalidParameters::TooSmallToWrap { minimum: 5 })
+        ));
+        let wrapped = Parameters::new(5, 5, 1).unwrap().with_wrap().unwrap();
+        assert!(matches!(
+            wrapped.with_topology(Topology::Hexagonal),
+            Err(InvalidParameters::OddRowsToWrap)
+        ));
+        let custom = Parameters::new(9, 9, 10)
+            .unwrap()
+            .with_neighbourhood(radius)
+            .unwrap();
+        assert!(matches!(
+            custom.with_topology(Topology::Hexagonal),
+            Err(InvalidParameters::NeighbourhoodNotSquare)
+        ));
+    }
+
+    #[test]
+    fn conflicting_arguments_are_explained() {
+        let cases = [
+            (
+                "--load game.save --rows 9",
+                "--load can't be combined with other options",
+            ),
+            ("--beginner --expert", "Only one preset can be given"),
+            (
+                "--beginner --mines 5",
+                "A preset can't be combined with --rows, --columns or --mines",
+            ),
+            (
+                "--mask heart --columns 9",
+                "A mask can't be combined with a preset, --rows or --columns",
+            ),
+            (
+                "--attempts 5",
+                "--attempts can only be used with --no-guess",
+            ),
+            ("--rows 9 --rows=10", "--rows given more than once"),
+            ("--wrap --wrap", "--wrap given more than once"),
+            ("--rows nine", "Invalid value \"nine\" for --rows"),
+            ("--mines", "Missing value for --mines"),
+            ("--size 9", "Unknown argument --size"),
+            ("replay game.rec verify", "Unknown argument verify"),
+            (
+                "replay --speed 0 game.rec",
+                "Invalid value \"0\" for --speed",
+            ),
+            ("verify game.rec replay", "Unknown argument replay"),
+            ("verify", "Missing value for verify"),
+        ];
+        for (args, message) in cases {
+            assert_eq!(error(args), message, "{}", args);
+        }
+    }
+
+    #[test]
+    fn options_about_the_frontend_can_go_with_load() {
+        let args = "--load game.save --line --colors none".split_whitespace();
+        match parse_arguments(args.map(String::from)) {
+            Ok(Arguments::Load(path, options)) => {
+                assert_eq!(path, PathBuf::from("game.save"));
+                assert!(options.line());
+                assert_eq!(options.colors(), Some(Depth::Plain));
+            }
+            _ => panic!("--load wasn't accepted"),
+        }
+    }
 }
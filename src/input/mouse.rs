@@ -0,0 +1,148 @@
+//! Turns mouse clicks into plays, for frontends with mouse support.
+//!
+//! Like on the classic game:
+//!  - the left button digs;
+//!  - the right button marks, or unmarks a marked cell;
+//!  - the middle button, or both the left and the right buttons, chord.
+//!
+//! Digs and chords are done when the button is released, so pressing
+//!  the other button first turns a dig into a chord.
+//! Only a dig builds the board, so the other plays are refused before
+//!  it, see [Session::apply](crate::game::Session::apply).
+//! Mapping the position of the click to a cell is up to the frontend.
+//!
+
+use crate::board::{Board, CellView};
+use crate::input::plays::{Play, PlayMode};
+
+/// Mouse buttons
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Button {
+    /// Digs
+    Left,
+    /// Marks and unmarks
+    Right,
+    /// Chords
+    Middle,
+}
+
+/// A button pressed or released
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Click {
+    /// The button was pressed
+    Press(Button),
+    /// The button was released
+    Release(Button),
+}
+
+/// Play that marks a cell, or unmarks it if it is already marked.
+///
+/// The cell has the rows of all the layers numbered one after another,
+///  as on the [Board], and the play has its [layer](Play::layer).
+pub fn flag(board: &Board, cell: (u16, u16)) -> Play {
+    let mode = match board.cell_view(cell) {
+        CellView::Marked => PlayMode::Unmark,
+        _ => PlayMode::Mark,
+    };
+    return Play::layered(mode, cell, board.rows());
+}
+
+/// Buttons being held, to detect the left and right buttons pressed together.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub struct Buttons {
+    /// If the left button is down
+    left: bool,
+    /// If the right button is down
+    right: bool,
+    /// If both buttons were down since they were last released together,
+    ///  so the release chords
+    both: bool,
+}
+
+impl Buttons {
+    /// Constructor
+    pub fn new() -> Buttons {
+        return Buttons::default();
+    }
+
+    /// Updates the buttons held and returns the play of the click, if any.
+    ///
+    /// The cell is the one under the mouse, `None` if the click was
+    ///  outside of the board, which cancels the play. Its layer is
+    ///  found as on [flag].
+    pub fn click(&mut self, board: &Board, click: Click, cell: Option<(u16, u16)>) -> Option<Play> {
+        let mode = match click {
+            Click::Press(Button::Left) => {
+                self.left = true;
+                self.both |= self.right;
+                None
+            }
+            Click::Press(Button::Right) => {
+                self.right = true;
+                self.both |= self.left;
+                match self.both {
+                    true => None,
+                    false => return cell.map(|cell| flag(board, cell)),
+                }
+            }
+            Click::Press(Button::Middle) => None,
+            Click::Release(Button::Middle) => Some(PlayMode::Chord),
+            Click::Release(button) => {
+                let held = match button {
+                    Button::Left => &mut self.left,
+                    _ => &mut self.right,
+                };
+                // ex: the press happened before the frontend started listening
+                if !*held {
+                    return None;
+                }
+                *held = false;
+
+                match (self.both, button) {
+                    // the first button released chords, the other does nothing
+                    (true, _) if self.left || self.right => Some(PlayMode::Chord),
+                    (true, _) => {
+                        self.both = false;
+                        None
+                    }
+                    (false, Button::Left) => Some(PlayMode::Dig),
+                    (false, _) => None,
+                }
+            }
+        };
+
+        return Some(Play::layered(mode?, cell?, board.rows()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Notice, Session};
+    use crate::input::arguments::Parameters;
+    use crate::input::plays::Command;
+
+    #[test]
+    fn right_click_before_the_first_dig_does_not_dig() {
+        let params = Parameters::new(9, 9, 10).unwrap().with_seed(1);
+        let mut session = Session::new(&params);
+        let mut buttons = Buttons::new();
+
+        let play = buttons
+            .click(session.board(), Click::Press(Button::Right), Some((4, 4)))
+            .unwrap();
+        assert_eq!(*play.mode(), PlayMode::Mark);
+        let notices = session.apply(Command::Play(play));
+        assert!(matches!(notices[..], [Notice::DigFirst]));
+        assert!(!session.is_built());
+        assert_eq!(session.board().cell_view((4, 4)), CellView::Hidden);
+
+        buttons.click(session.board(), Click::Release(Button::Right), Some((4, 4)));
+        buttons.click(session.board(), Click::Press(Button::Left), Some((4, 4)));
+        let play = buttons
+            .click(session.board(), Click::Release(Button::Left), Some((4, 4)))
+            .unwrap();
+        session.apply(Command::Play(play));
+        assert!(session.is_built());
+    }
+}
@@ -5,16 +5,33 @@
 //!  coordinates to the target cell, ex:
 //!  `m 3 3`
 //!
+//! On boards with layers the layer of the cell follows its column,
+//!  ex: `m 3 3 1`, without it the cell is on the first layer (0).
+//!
 //! The first play only requests the target cell.
 //!
+//! Besides plays, the following plays can also be commands:
+//!  - `p`: show the probability of each undug cell having a mine
+//!  - `undo`: revert the last play
+//!  - `redo`: apply again the last undone play
+//!  - `save <file>`: save the game to a file
+//!  - `load <file>`: replace the game by the one saved on a file
+//!  - `describe [<row> <column> [<layer>]]`, `describe row [<row>]`,
+//!    `describe column [<column>]`, `neighbours [of] [<row> <column> [<layer>]]`
+//!    and `status`: describe the board in words, without coordinates
+//!    they use the last cell played or described
+//!  - `q`: quit the game (same as the end of the input)
+//!
 
 use std::io::Write;
+use std::path::PathBuf;
 use std::{fmt, io};
 
 use std::error::Error;
 use std::fmt::Formatter;
 
 /// Possible plays
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub enum PlayMode {
     /// Show an undug cell.
     ///
@@ -27,35 +44,142 @@ pub enum PlayMode {
     Mark,
     /// Unmark a cell as a mine
     Unmark,
+    /// Dig all the unmarked neighbours of a dug number,
+    ///  if it has as many marked neighbours as its number.
+    ///
+    /// Digging a dug number does the same.
+    Chord,
 }
 
 /// Built after reading play input from the user
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub struct Play {
     /// Action to apply on the target cell
     mode: PlayMode,
     /// coordinates of the cell to act on
-    ///
-    /// TODO The option is to avoid having
-    ///  to deal with lifetimes. This can
-    ///  be transformed into reference
-    cell: Option<(u8, u8)>,
+    cell: (u16, u16),
+    /// layer of the cell, 0 on boards without layers
+    layer: u16,
 }
 
-/// Just getters
+/// Constructor + Getters
 impl Play {
+    /// Constructor
+    ///
+    /// Used by frontends that don't read plays from the standard input.
+    ///
+    /// The cell is on the first layer, see [with_layer](Play::with_layer).
+    pub fn new(mode: PlayMode, cell: (u16, u16)) -> Play {
+        return Play {
+            mode,
+            cell,
+            layer: 0,
+        };
+    }
+
+    /// Constructor from a cell with the rows of all the layers numbered
+    ///  one after another, as the [Board](crate::Board) numbers them,
+    ///  the opposite of [unlayered](Play::unlayered).
+    pub fn layered(mode: PlayMode, (row, column): (u16, u16), rows: u16) -> Play {
+        return Play::new(mode, (row % rows, column)).with_layer(row / rows);
+    }
+
+    /// Set the layer of the cell
+    pub fn with_layer(mut self, layer: u16) -> Play {
+        self.layer = layer;
+        return self;
+    }
+
     /// Mode getter
     pub fn mode(&self) -> &PlayMode {
         return &self.mode;
     }
 
     /// Cell getter
+    pub fn cell(&self) -> (u16, u16) {
+        return self.cell;
+    }
+
+    /// Layer getter
+    pub fn layer(&self) -> u16 {
+        return self.layer;
+    }
+
+    /// The same play on the first layer, with the rows of the layers
+    ///  below the one of the cell added to its row, as the
+    ///  [Board](crate::Board) numbers the rows of all its layers.
     ///
-    /// TODO turn return value into reference and deal with lifetimes
-    pub fn cell(&mut self) -> (u8, u8) {
-        return self.cell.take().unwrap();
+    /// `None` if the row or the layer are outside of a board
+    ///  with that many rows on each layer and layers.
+    pub fn unlayered(&self, rows: u16, layers: u16) -> Option<Play> {
+        if self.cell.0 >= rows || self.layer >= layers {
+            return None;
+        }
+        let row = self.layer as u32 * rows as u32 + self.cell.0 as u32;
+        if row > u16::MAX as u32 {
+            return None;
+        }
+        return Some(Play::new(self.mode, (row as u16, self.cell.1)));
     }
 }
 
+/// Same format as the input, ex: `m 3 3`, the layer is only written if it isn't 0
+impl fmt::Display for Play {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mode = match self.mode {
+            PlayMode::Dig => "d",
+            PlayMode::Mark => "m",
+            PlayMode::Unmark => "u",
+            PlayMode::Chord => "c",
+        };
+        write!(f, "{} {} {}", mode, self.cell.0, self.cell.1)?;
+        if self.layer > 0 {
+            write!(f, " {}", self.layer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Everything that can be asked after the first play
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Command {
+    /// A play over a cell
+    Play(Play),
+    /// Show the probability of each undug cell having a mine
+    Probabilities,
+    /// Revert the last play
+    Undo,
+    /// Apply again the last undone play
+    Redo,
+    /// Save the game to the file
+    Save(PathBuf),
+    /// Replace the game by the one saved on the file
+    Load(PathBuf),
+    /// Describe part of the board in words
+    Describe(Query),
+    /// End the game
+    Quit,
+}
+
+/// Parts of the board that can be described in words,
+///  see [accessible](crate::accessible).
+///
+/// Without coordinates the last cell played or described is used.
+///  Cells are given by their row, column and layer.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Query {
+    /// A cell
+    Cell(Option<(u16, u16, u16)>),
+    /// A row of the layer of the last cell
+    Row(Option<u16>),
+    /// A column of the layer of the last cell
+    Column(Option<u16>),
+    /// The neighbours of a cell
+    Neighbours(Option<(u16, u16, u16)>),
+    /// Mines left, cells to dig and time
+    Status,
+}
+
 /// Base Error struct
 ///
 /// TODO create more specific errors. Is this possible?
@@ -71,21 +195,23 @@ impl fmt::Display for InvalidPlay {
 }
 
 /// Get the play string from the user input
-fn read_play(input: &mut String) {
+///
+/// Returns `false` if the input ended.
+fn read_play(input: &mut String) -> bool {
     print!("play: ");
-    io::stdout().flush();
-    io::stdin().read_line(input);
+    io::stdout().flush().expect("Failed to flush stdout");
+    return io::stdin().read_line(input).expect("Failed to read play") > 0;
 }
 
 /// Parse cell on the play string
 ///
 /// TODO return a more specific error
-fn handle_cell(inputs: &Vec<&str>, first_index: u8) -> Result<(u8, u8), InvalidPlay> {
-    let row = match inputs[first_index as usize].parse::<u8>() {
+fn handle_cell(inputs: &[&str], first_index: usize) -> Result<(u16, u16), InvalidPlay> {
+    let row = match inputs[first_index].parse::<u16>() {
         Ok(v) => v,
         Err(_) => return Err(InvalidPlay {}),
     };
-    let column = match inputs[(first_index + 1) as usize].parse::<u8>() {
+    let column = match inputs[first_index + 1].parse::<u16>() {
         Ok(v) => v,
         Err(_) => return Err(InvalidPlay {}),
     };
@@ -93,51 +219,130 @@ fn handle_cell(inputs: &Vec<&str>, first_index: u8) -> Result<(u8, u8), InvalidP
     return Ok((row, column));
 }
 
+/// Parse cell and its optional layer on the play string,
+///  as row, column and layer
+fn handle_position(inputs: &[&str], first_index: usize) -> Result<(u16, u16, u16), InvalidPlay> {
+    let (row, column) = handle_cell(inputs, first_index)?;
+    let layer = match inputs.get(first_index + 2) {
+        Some(layer) => layer.parse::<u16>().map_err(|_| InvalidPlay)?,
+        None => 0,
+    };
+
+    return Ok((row, column, layer));
+}
+
 /// The first play only contains the target cell and the play
 ///  mode is always assumed its [Dig](PlayMode::Dig)
-pub fn read_first_play() -> Result<Play, InvalidPlay> {
-    let mut input = String::new();
-    read_play(&mut input);
+pub fn parse_first_play(input: &str) -> Result<Play, InvalidPlay> {
     let clean_input = input.trim().to_lowercase();
     let inputs: Vec<&str> = clean_input.split_whitespace().collect();
 
-    /// TODO return more specific error
-    if inputs.len() != 2 {
+    // TODO return more specific error
+    if inputs.len() != 2 && inputs.len() != 3 {
         return Err(InvalidPlay {});
     }
 
-    let cell = handle_cell(&inputs, 0)?;
+    let (row, column, layer) = handle_position(&inputs, 0)?;
 
     return Ok(Play {
         mode: PlayMode::Dig,
-        cell: Some(cell),
+        cell: (row, column),
+        layer,
     });
 }
 
-/// Following plays must contain both the play mode and the target cell
-pub fn read_following_plays() -> Result<Play, InvalidPlay> {
-    let mut input = String::new();
-    read_play(&mut input);
+/// Following plays must contain both the play mode and the target cell,
+///  unless they are a command without cell.
+pub fn parse_following_play(input: &str) -> Result<Command, InvalidPlay> {
+    // paths keep their case and spaces
+    if let Some((command, path)) = input.trim().split_once(char::is_whitespace) {
+        match command.to_lowercase().as_str() {
+            "save" => return Ok(Command::Save(PathBuf::from(path.trim()))),
+            "load" => return Ok(Command::Load(PathBuf::from(path.trim()))),
+            _ => {}
+        }
+    }
+
     let clean_input = input.trim().to_lowercase();
     let inputs: Vec<&str> = clean_input.split_whitespace().collect();
 
-    /// TODO return a more specific error
-    if inputs.len() != 3 {
+    let describe = |query| Ok(Command::Describe(query));
+    match inputs[..] {
+        ["describe"] => return describe(Query::Cell(None)),
+        ["describe", "row"] => return describe(Query::Row(None)),
+        ["describe", "column"] => return describe(Query::Column(None)),
+        ["describe", "row", row] => {
+            return describe(Query::Row(Some(row.parse().map_err(|_| InvalidPlay)?)))
+        }
+        ["describe", "column", column] => {
+            return describe(Query::Column(Some(
+                column.parse().map_err(|_| InvalidPlay)?,
+            )))
+        }
+        ["describe", _, _] | ["describe", _, _, _] => {
+            return describe(Query::Cell(Some(handle_position(&inputs, 1)?)))
+        }
+        ["neighbours"] | ["neighbors"] => return describe(Query::Neighbours(None)),
+        ["neighbours", "of", _, _]
+        | ["neighbors", "of", _, _]
+        | ["neighbours", "of", _, _, _]
+        | ["neighbors", "of", _, _, _] => {
+            return describe(Query::Neighbours(Some(handle_position(&inputs, 2)?)))
+        }
+        ["neighbours", _, _]
+        | ["neighbors", _, _]
+        | ["neighbours", _, _, _]
+        | ["neighbors", _, _, _] => {
+            return describe(Query::Neighbours(Some(handle_position(&inputs, 1)?)))
+        }
+        ["status"] => return describe(Query::Status),
+        ["p"] => return Ok(Command::Probabilities),
+        ["undo"] => return Ok(Command::Undo),
+        ["redo"] => return Ok(Command::Redo),
+        ["q"] => return Ok(Command::Quit),
+        _ => {}
+    }
+
+    // TODO return a more specific error
+    if inputs.len() != 3 && inputs.len() != 4 {
         return Err(InvalidPlay {});
     }
 
-    let cell = handle_cell(&inputs, 1)?;
+    let (row, column, layer) = handle_position(&inputs, 1)?;
 
-    /// TODO return a more specific error
+    // TODO return a more specific error
     let mode = match inputs[0] {
         "u" => PlayMode::Unmark,
         "m" => PlayMode::Mark,
         "d" => PlayMode::Dig,
+        "c" => PlayMode::Chord,
         _ => return Err(InvalidPlay {}),
     };
 
-    Ok(Play {
+    Ok(Command::Play(Play {
         mode,
-        cell: Some(cell),
-    })
+        cell: (row, column),
+        layer,
+    }))
+}
+
+/// Reads the first play from the user, see [parse_first_play]
+///
+/// Can only be a [Play](Command::Play) or, if the input
+///  ended or the user entered `q`, [Quit](Command::Quit).
+pub fn read_first_play() -> Result<Command, InvalidPlay> {
+    let mut input = String::new();
+    if !read_play(&mut input) || input.trim() == "q" {
+        return Ok(Command::Quit);
+    }
+    return parse_first_play(&input).map(Command::Play);
+}
+
+/// Reads a following play from the user, see [parse_following_play]
+pub fn read_following_plays() -> Result<Command, InvalidPlay> {
+    let mut input = String::new();
+    if !read_play(&mut input) {
+        return Ok(Command::Quit);
+    }
+    return parse_following_play(&input);
 }
@@ -0,0 +1,42 @@
+//! Minesweeper Game - engine.
+//!
+//! Contains all the game logic so it can be used by
+//!  other frontends (bots, servers, analysis tools...)
+//!  besides the CLI one on `main.rs`.
+//!
+//! The stable API is re-exported on the root of the crate:
+//!  - [Board]: the game state and the plays that change it;
+//!  - [GameResult]: returned after each dig;
+//!  - [CellView]: what a player can see of a cell;
+//!  - [Parameters] and [Preset]: the game variables used to create a [Board];
+//!  - [Topology]: the shape of the cells and which ones are neighbours;
+//!  - [Play] and [PlayMode]: a play over a cell.
+//!
+//! The [solver] module deduces the safe cells and the mines
+//!  from what the player can see of a [Board], and the [analysis]
+//!  module computes the probability of each cell having a mine.
+//!
+//! The [game] module applies the commands of a player to a [Board],
+//!  it's the play pipeline shared by the frontends.
+//!
+//! The [stats] module keeps the lifetime statistics of the finished games.
+//!
+//! The [theme] module has the colours used to print the board,
+//!  and the [accessible] module describes it in words instead.
+//!
+
+#![allow(clippy::needless_return)]
+
+pub mod accessible;
+pub mod analysis;
+pub mod board;
+pub mod game;
+pub mod input;
+pub mod solver;
+pub mod stats;
+pub mod theme;
+
+pub use crate::board::topology::Topology;
+pub use crate::board::{Board, CellView, GameResult};
+pub use crate::input::arguments::{Parameters, Preset};
+pub use crate::input::plays::{Play, PlayMode};
@@ -1,69 +1,392 @@
 //! Minesweeper Game - CLI version.
 //!
+//! Thin frontend over the [mineswipper] library, that plays on a
+//!  full-screen interface (see [tui]) or reads the plays
+//!  line by line from the standard input.
+//!
 //! Known bugs:
 //!  1. some times after the first play only one cell is displayed
 //!  2. a mine can be spawned at a position where in all straight
-//!   (vertical and horizontal) directions there is also mines or a wall
-//!
-//! TODO:
-//!  1. Show number of remaining mines to mark and
-//!   change [mark](board::Board::mark) logic.
+//!     (vertical and horizontal) directions there is also mines or a wall
 //!
 
-mod board;
-mod input;
+#![allow(clippy::needless_return)]
 
-use crate::board::{Board, GameResult};
-use crate::input::arguments::{read_parameters, Parameters};
-use crate::input::plays::{read_first_play, read_following_plays, PlayMode};
+use std::io::{self, BufRead};
+use std::path::Path;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::{process, thread};
 
-fn main() {
-    //let a = read_parameters();
-    let params = Parameters::new(15, 30, 50);
+use crossterm::tty::IsTty;
 
-    let mut board = Board::new(&params);
+use mineswipper::accessible::{
+    describe, describe_board, describe_history, describe_play, describe_probabilities,
+    describe_result, describe_status,
+};
+use mineswipper::analysis::mine_probabilities;
+use mineswipper::board::replay::{Player, Replay};
+use mineswipper::board::verify::verify;
+use mineswipper::game::{Notice, Session};
+use mineswipper::input::arguments::{read_arguments, Arguments, Options, USAGE};
+use mineswipper::input::plays::{read_first_play, read_following_plays, Command};
+use mineswipper::stats::{default_path, Store, STATS_ENV};
+use mineswipper::theme::{self, Depth, Palette, Theme};
+use mineswipper::{Board, GameResult};
 
-    loop {
+mod tui;
+
+/// Prints what the player should be told, one notice per line.
+fn print_notices(notices: &[Notice]) {
+    for notice in notices {
+        println!("{}", notice);
+    }
+}
+
+/// Reads the plays line by line until the player quits or the game ends.
+///
+/// The first play only needs the cell, see [read_first_play].
+/// On accessible mode what each play changed is described
+///  in words instead of printing the board.
+fn play_lines(session: &mut Session, accessible: bool) {
+    // cell used by the descriptions without coordinates
+    let mut cursor = (0, 0);
+
+    match (session.is_built(), accessible) {
+        (true, true) => println!(
+            "{}, {}",
+            describe_board(session.board()),
+            describe_result(session.board())
+        ),
+        (true, false) => println!("{}", session.board()),
+        (false, true) => println!(
+            "{}, enter the row and column{} of the first cell to dig",
+            describe_board(session.board()),
+            match session.board().layers() {
+                1 => "",
+                _ => " and the layer",
+            }
+        ),
+        (false, false) => {}
+    }
+    while !session.is_built() {
+        let before = session.board().cells_to_dig() as i64;
         match read_first_play() {
-            Ok(mut play) => {
-                board.build(play.cell());
-                println!("{}", board);
-                break;
+            Ok(Command::Quit) => return,
+            Ok(Command::Play(play)) => {
+                print_notices(&session.apply(Command::Play(play)));
+                let board = session.board();
+                match (session.is_built(), accessible) {
+                    (true, true) => {
+                        cursor = play
+                            .unlayered(board.rows(), board.layers())
+                            .unwrap_or(play)
+                            .cell();
+                        let revealed = before - board.cells_to_dig() as i64;
+                        println!("{}", describe_play(board, &play, revealed));
+                    }
+                    (true, false) => println!("{}", board),
+                    (false, _) => {}
+                }
             }
+            Ok(_) => {}
             Err(e) => println!("{}", e),
-        };
+        }
     }
 
     loop {
-        match read_following_plays() {
-            Ok(mut play) => {
-                match play.mode() {
-                    PlayMode::Dig => {
-                        let end = match board.dig(play.cell()) {
-                            GameResult::Won => {
-                                println!("You won");
-                                true
-                            }
-                            GameResult::Lost => {
-                                println!("You lost");
-                                true
-                            }
-                            GameResult::Continue => false,
-                        };
-
-                        if end {
-                            break;
-                        }
+        let command = match read_following_plays() {
+            Ok(Command::Quit) => return,
+            Ok(Command::Probabilities) if accessible => {
+                println!("{}", describe_probabilities(session.board(), cursor));
+                continue;
+            }
+            Ok(Command::Probabilities) => {
+                println!("{}", mine_probabilities(session.board()));
+                continue;
+            }
+            Ok(Command::Describe(query)) => {
+                println!("{}", describe(session.board(), query, &mut cursor));
+                continue;
+            }
+            Ok(command) => command,
+            Err(e) => {
+                println!("{}", e);
+                continue;
+            }
+        };
+
+        let before = session.board().cells_to_dig() as i64;
+        let notices = session.apply(command.clone());
+        print_notices(&notices);
+        if matches!(command, Command::Save(_))
+            || notices
+                .iter()
+                .any(|n| matches!(n, Notice::OutsideBoard | Notice::Blocked))
+        {
+            continue;
+        }
+
+        let board = session.board();
+        if accessible {
+            let revealed = before - board.cells_to_dig() as i64;
+            match &command {
+                Command::Play(play) => {
+                    cursor = play
+                        .unlayered(board.rows(), board.layers())
+                        .unwrap_or(*play)
+                        .cell();
+                    println!("{}", describe_play(board, play, revealed));
+                }
+                Command::Undo | Command::Redo => {
+                    println!("{}", describe_history(board, revealed))
+                }
+                _ => println!("{}", describe_status(board)),
+            }
+        }
+        match board.result() {
+            GameResult::Won => println!("You won"),
+            GameResult::Lost => println!("You lost"),
+            GameResult::Continue => {
+                if !accessible {
+                    println!("{}", board);
+                }
+                continue;
+            }
+        }
+
+        if !board.practice_mode() {
+            return;
+        }
+        println!("Practice mode: enter undo to keep playing or q to quit");
+    }
+}
+
+/// Uses the theme and colors of the options to print the board.
+///
+/// Exits if the theme can't be loaded.
+fn use_theme(options: &Options) {
+    let palette = match options.theme().map(Palette::find) {
+        None => Palette::CLASSIC,
+        Some(Ok(palette)) => palette,
+        Some(Err(e)) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    };
+    // the meaning of the descriptions never depends on colours
+    let depth = match options.accessible() {
+        true => Depth::Plain,
+        false => options.colors().unwrap_or_else(Depth::detect),
+    };
+    theme::set_current(Theme::new(palette, depth));
+}
+
+fn main() {
+    theme::set_current(Theme::new(Palette::CLASSIC, Depth::detect()));
+
+    let (session, options) = match read_arguments() {
+        Ok(Arguments::Play(params, options)) => {
+            let session = match options.record() {
+                Some(path) => Session::new(&params).with_record(path.clone()),
+                None => Session::new(&params),
+            };
+            (session, options)
+        }
+        Ok(Arguments::Load(path, options)) => match Board::load(&path) {
+            Ok(board) => (Session::resume(board), options),
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        },
+        Ok(Arguments::Replay {
+            path,
+            speed,
+            paused,
+        }) => {
+            play_back(&path, speed, paused);
+            return;
+        }
+        Ok(Arguments::Verify(path)) => {
+            let replay = match Replay::load(&path) {
+                Ok(replay) => replay,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                }
+            };
+            let verification = verify(&replay);
+            println!("{}", verification);
+            if !verification.is_valid() {
+                process::exit(1);
+            }
+            return;
+        }
+        Ok(Arguments::Stats) => {
+            let store = match default_path() {
+                Some(path) => Store::new(path),
+                None => {
+                    eprintln!("Can't find the statistics file, set {}", STATS_ENV);
+                    process::exit(1);
+                }
+            };
+            match store.load() {
+                Ok(stats) => {
+                    if stats.corrupted() > 0 {
+                        println!(
+                            "Dropped {} corrupted records, the original file was kept with a .corrupt suffix",
+                            stats.corrupted()
+                        );
                     }
-                    PlayMode::Mark => board.mark(play.cell()),
-                    PlayMode::Unmark => board.unmark(play.cell()),
-                };
+                    println!("{}", stats);
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                }
+            }
+            return;
+        }
+        Ok(Arguments::Help) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    use_theme(&options);
+    let mut session = match default_path() {
+        Some(path) => session.with_stats(Store::new(path)),
+        None => session,
+    };
 
-                println!("{}", board);
+    let full_screen =
+        !options.line() && !options.accessible() && io::stdin().is_tty() && io::stdout().is_tty();
+    let full_screen = full_screen
+        && match tui::play(&mut session) {
+            Ok(()) => true,
+            Err(e) => {
+                // the line mode still works on terminals without support
+                println!("{}", e);
+                false
             }
-            Err(e) => println!("{}", e),
         };
+    if !full_screen {
+        play_lines(&mut session, options.accessible());
+    }
+
+    // quit before the first play
+    if !session.is_built() {
+        return;
     }
 
-    board.finish();
+    if full_screen {
+        match session.board().result() {
+            GameResult::Won => println!("You won"),
+            GameResult::Lost => println!("You lost"),
+            GameResult::Continue => {}
+        }
+    }
+    let (board, notices) = session.end();
+    if options.accessible() {
+        // the summary without the board
+        if let Some(seed) = board.seed() {
+            println!("seed: {}", seed);
+        }
+        println!("{}", board.summary());
+    } else {
+        board.finish();
+    }
+    print_notices(&notices);
+}
+
+/// Plays a recorded game again, showing the board after each play.
+///
+/// Reads commands while playing: `p` pauses or resumes, enter (or `s`)
+///  applies a single play and pauses, `q` stops.
+fn play_back(path: &Path, speed: f64, mut paused: bool) {
+    let replay = match Replay::load(path) {
+        Ok(replay) => replay,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+
+    // stdin is read on another thread so it doesn't block the playback
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            match line {
+                Ok(line) if sender.send(line.trim().to_lowercase()).is_ok() => {}
+                _ => break,
+            }
+        }
+    });
+
+    let mut player = Player::new(&replay);
+    let total = replay.events().len();
+    println!("{}", player.board());
+    println!("p: pause/resume, enter: next play, q: quit");
+
+    let mut input_closed = false;
+    while let Some(delay) = player.delay() {
+        let delay = delay.div_f64(speed);
+        let command = match (paused, input_closed) {
+            (true, _) => match receiver.recv() {
+                Ok(command) => Some(command),
+                // nothing else can be read, so stop
+                Err(_) => break,
+            },
+            (false, true) => {
+                thread::sleep(delay);
+                None
+            }
+            (false, false) => match receiver.recv_timeout(delay) {
+                Ok(command) => Some(command),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => {
+                    input_closed = true;
+                    continue;
+                }
+            },
+        };
+
+        match command.as_deref() {
+            None => {}
+            Some("q") => return,
+            Some("p") => {
+                paused = !paused;
+                println!("{}", if paused { "Paused" } else { "Resumed" });
+                continue;
+            }
+            Some("") | Some("s") => paused = true,
+            Some(_) => {
+                println!("p: pause/resume, enter: next play, q: quit");
+                continue;
+            }
+        }
+
+        // there is always a next play if there is a delay
+        let event = player.step().unwrap();
+        println!(
+            "play {}/{} at {:.1}s: {}",
+            player.played(),
+            total,
+            event.time().as_secs_f64(),
+            event.action()
+        );
+        println!("{}", player.board());
+    }
+
+    match player.board().result() {
+        GameResult::Won => println!("You won"),
+        GameResult::Lost => println!("You lost"),
+        GameResult::Continue => println!("The game was not finished"),
+    }
+    player.board().finish();
 }
@@ -0,0 +1,431 @@
+//! Logical solver over the state of the board that the player can see.
+//!
+//! Only uses what a [CellView] exposes: the numbers of the dug cells,
+//!  the undug cells and the total number of mines. The hidden values
+//!  of the board are never looked at.
+//!
+//! Each dug number gives a constraint: "of these undug neighbours,
+//!  exactly N are mines". The following rules are applied until
+//!  nothing new can be deduced:
+//!  1. single cell: a constraint with no mines left makes all its cells
+//!     safe and one with as many mines as cells makes all of them mines;
+//!  2. subset reduction: if the cells of a constraint are all in another
+//!     one, the cells only in the bigger one hold the difference of mines;
+//!  3. global mine count: if the remaining mines are all accounted by
+//!     disjoint constraints, every other undug cell is safe (and the
+//!     same for the trivial cases of no mines or only mines left).
+//!
+//! Flags are not trusted, since the player can be wrong. A flagged cell
+//!  is treated as any other undug cell, so it can be proved either a mine
+//!  or safe (a wrong flag).
+//!
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::board::{Board, CellView};
+
+/// Cells that are provably safe or provably a mine.
+#[derive(Debug, Default)]
+pub struct Deductions {
+    /// Undug cells that can't have a mine
+    safe: Vec<(u16, u16)>,
+    /// Undug cells that must have a mine
+    mines: Vec<(u16, u16)>,
+}
+
+/// Just getters
+impl Deductions {
+    /// Safe cells getter
+    pub fn safe(&self) -> &[(u16, u16)] {
+        return &self.safe;
+    }
+
+    /// Mines getter
+    pub fn mines(&self) -> &[(u16, u16)] {
+        return &self.mines;
+    }
+
+    /// If nothing could be deduced
+    pub fn is_empty(&self) -> bool {
+        return self.safe.is_empty() && self.mines.is_empty();
+    }
+}
+
+/// "Exactly `mines` of `cells` are mines".
+///
+/// The cells are indexes of the undug cells and are kept sorted,
+///  so equal constraints are only stored once.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub(crate) struct Constraint {
+    pub(crate) cells: Vec<usize>,
+    pub(crate) mines: u32,
+}
+
+impl Constraint {
+    /// If all the cells of self are also on other
+    fn is_subset_of(&self, other: &Constraint) -> bool {
+        return self.cells.len() <= other.cells.len()
+            && self
+                .cells
+                .iter()
+                .all(|cell| other.cells.binary_search(cell).is_ok());
+    }
+}
+
+/// State of the deduction process.
+struct Solver {
+    /// Coordinates of the undug cells
+    unknown: Vec<(u16, u16)>,
+    /// What is already known about each undug cell:
+    ///  `Some(true)` if it is a mine, `Some(false)` if it's safe.
+    known: Vec<Option<bool>>,
+    /// Current constraints, only over cells that are not known yet
+    constraints: BTreeSet<Constraint>,
+    /// Total number of mines on the board
+    mines_count: u32,
+}
+
+impl Solver {
+    /// Constructor
+    fn new(board: &Board) -> Solver {
+        let (unknown, constraints) = visible_constraints(board);
+
+        return Solver {
+            known: vec![None; unknown.len()],
+            unknown,
+            constraints: constraints.into_iter().collect(),
+            mines_count: board.mines_count(),
+        };
+    }
+
+    /// Apply the rules until nothing new is deduced.
+    fn run(&mut self) {
+        loop {
+            self.simplify();
+            if self.single_cell() || self.subsets() || self.global() {
+                continue;
+            }
+            break;
+        }
+    }
+
+    /// Auxiliary method to store a deduction.
+    ///
+    /// Returns if it was something new.
+    fn learn(&mut self, cell: usize, mine: bool) -> bool {
+        if self.known[cell].is_some() {
+            return false;
+        }
+        self.known[cell] = Some(mine);
+        return true;
+    }
+
+    /// Removes the known cells from the constraints,
+    ///  discarding the ones left without cells.
+    fn simplify(&mut self) {
+        let known = &self.known;
+        self.constraints = self
+            .constraints
+            .iter()
+            .map(|constraint| {
+                let mines = constraint
+                    .cells
+                    .iter()
+                    .filter(|cell| known[**cell] == Some(true))
+                    .count() as u32;
+                Constraint {
+                    cells: constraint
+                        .cells
+                        .iter()
+                        .copied()
+                        .filter(|cell| known[*cell].is_none())
+                        .collect(),
+                    mines: constraint.mines - mines,
+                }
+            })
+            .filter(|constraint| !constraint.cells.is_empty())
+            .collect();
+    }
+
+    /// Rule 1: constraints with all or none of the cells being mines.
+    fn single_cell(&mut self) -> bool {
+        let mut learned = Vec::new();
+        for constraint in self.constraints.iter() {
+            if constraint.mines == 0 {
+                learned.extend(constraint.cells.iter().map(|cell| (*cell, false)));
+            } else if constraint.mines as usize == constraint.cells.len() {
+                learned.extend(constraint.cells.iter().map(|cell| (*cell, true)));
+            }
+        }
+
+        let mut changed = false;
+        for (cell, mine) in learned {
+            changed |= self.learn(cell, mine);
+        }
+        return changed;
+    }
+
+    /// Rule 2: constraints that contain other constraints.
+    ///
+    /// Returns if new constraints were added.
+    fn subsets(&mut self) -> bool {
+        // a constraint can only contain another if it contains
+        //  the first cell of the other one
+        let mut by_cell = HashMap::<usize, Vec<&Constraint>>::new();
+        for constraint in self.constraints.iter() {
+            for cell in constraint.cells.iter() {
+                by_cell.entry(*cell).or_default().push(constraint);
+            }
+        }
+
+        let mut derived = Vec::new();
+        for small in self.constraints.iter() {
+            for big in by_cell[&small.cells[0]].iter() {
+                if big.cells.len() == small.cells.len() || !small.is_subset_of(big) {
+                    continue;
+                }
+
+                let constraint = Constraint {
+                    cells: big
+                        .cells
+                        .iter()
+                        .copied()
+                        .filter(|cell| small.cells.binary_search(cell).is_err())
+                        .collect(),
+                    mines: big.mines - small.mines,
+                };
+                if !self.constraints.contains(&constraint) {
+                    derived.push(constraint);
+                }
+            }
+        }
+
+        let changed = !derived.is_empty();
+        self.constraints.extend(derived);
+        return changed;
+    }
+
+    /// Rule 3: reasoning with the total number of mines.
+    fn global(&mut self) -> bool {
+        let known_mines = self.known.iter().filter(|k| **k == Some(true)).count() as u32;
+        let remaining = self.mines_count - known_mines;
+        let left = (0..self.unknown.len())
+            .filter(|cell| self.known[*cell].is_none())
+            .collect::<Vec<usize>>();
+
+        if left.is_empty() {
+            return false;
+        }
+
+        if remaining == 0 || remaining as usize == left.len() {
+            for cell in left {
+                self.learn(cell, remaining != 0);
+            }
+            return true;
+        }
+
+        // pick constraints without cells in common, if their mines are
+        //  all the remaining ones, no other cell can have a mine
+        let mut covered = BTreeSet::new();
+        let mut mines = 0;
+        for constraint in self.constraints.iter() {
+            if constraint.cells.iter().all(|cell| !covered.contains(cell)) {
+                covered.extend(constraint.cells.iter().copied());
+                mines += constraint.mines;
+            }
+        }
+
+        let mut changed = false;
+        if mines == remaining {
+            for cell in left {
+                if !covered.contains(&cell) {
+                    changed |= self.learn(cell, false);
+                }
+            }
+        }
+        return changed;
+    }
+
+    /// Collects the deductions into the coordinates of the cells
+    fn deductions(&self) -> Deductions {
+        let mut deductions = Deductions::default();
+        for (cell, known) in self.unknown.iter().zip(self.known.iter()) {
+            match known {
+                Some(true) => deductions.mines.push(*cell),
+                Some(false) => deductions.safe.push(*cell),
+                None => {}
+            }
+        }
+        return deductions;
+    }
+}
+
+/// Collects the undug cells of the board and builds one
+///  constraint for each dug number with undug neighbours.
+///
+/// The cells of the constraints are indexes of the returned undug cells.
+pub(crate) fn visible_constraints(board: &Board) -> (Vec<(u16, u16)>, Vec<Constraint>) {
+    let mut unknown = Vec::new();
+    let mut index = HashMap::new();
+    let mut numbers = Vec::new();
+
+    for cell in board.cells() {
+        match board.cell_view(cell) {
+            CellView::Hidden | CellView::Marked => {
+                index.insert(cell, unknown.len());
+                unknown.push(cell);
+            }
+            CellView::Number(n) => numbers.push((cell, n)),
+            CellView::Empty | CellView::Mine | CellView::Blocked => {}
+        }
+    }
+
+    let mut constraints = Vec::new();
+    for (cell, n) in numbers {
+        let mut cells = board
+            .neighbours(cell)
+            .iter()
+            .filter_map(|neighbour| index.get(neighbour).copied())
+            .collect::<Vec<usize>>();
+        if !cells.is_empty() {
+            cells.sort_unstable();
+            constraints.push(Constraint {
+                cells,
+                mines: n as u32,
+            });
+        }
+    }
+
+    return (unknown, constraints);
+}
+
+/// Finds every undug cell that is provably safe or provably a mine.
+///
+/// See the [module](self) documentation for the rules used.
+pub fn solve(board: &Board) -> Deductions {
+    let mut solver = Solver::new(board);
+    solver.run();
+    return solver.deductions();
+}
+
+/// What is provable about each undug cell, in the order
+///  of the cells returned by [visible_constraints].
+pub(crate) fn deduce(board: &Board) -> Vec<Option<bool>> {
+    let mut solver = Solver::new(board);
+    solver.run();
+    return solver.known;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::from_grid;
+    use crate::board::GameResult;
+    use crate::input::arguments::Parameters;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// Auxiliary function to get what the solver knows about a cell.
+    fn known(solver: &Solver, cell: (u16, u16)) -> Option<bool> {
+        let index = solver.unknown.iter().position(|c| *c == cell).unwrap();
+        return solver.known[index];
+    }
+
+    #[test]
+    fn single_cell_finds_mines_and_then_safe_cells() {
+        // 0,0 only has 1,1 undug, then 0,1 has no mines left
+        let board = from_grid(&["oo..", "o*..", "....", ".*.*"]);
+        let mut solver = Solver::new(&board);
+
+        assert!(solver.single_cell());
+        assert_eq!(known(&solver, (1, 1)), Some(true));
+        assert_eq!(known(&solver, (0, 2)), None);
+
+        solver.simplify();
+        assert!(solver.single_cell());
+        for cell in [(0, 2), (1, 2), (2, 0), (2, 1)] {
+            assert_eq!(known(&solver, cell), Some(false));
+        }
+        assert_eq!(known(&solver, (2, 2)), None);
+    }
+
+    #[test]
+    fn subsets_reduce_the_bigger_constraint() {
+        // the mine of 0,1 is the one of 0,0
+        let board = from_grid(&["oo..", "*...", "...*"]);
+        let mut solver = Solver::new(&board);
+        assert!(!solver.single_cell());
+
+        assert!(solver.subsets());
+        solver.simplify();
+        assert!(solver.single_cell());
+        assert_eq!(known(&solver, (0, 2)), Some(false));
+        assert_eq!(known(&solver, (1, 2)), Some(false));
+        assert_eq!(known(&solver, (1, 0)), None);
+    }
+
+    #[test]
+    fn global_count_with_the_mines_accounted() {
+        // the only mine is next to the numbers
+        let board = from_grid(&["oo.", "*..", "..."]);
+        let mut solver = Solver::new(&board);
+
+        assert!(solver.global());
+        for cell in [(2, 0), (2, 1), (2, 2)] {
+            assert_eq!(known(&solver, cell), Some(false));
+        }
+        assert_eq!(known(&solver, (1, 0)), None);
+        assert_eq!(known(&solver, (1, 1)), None);
+    }
+
+    #[test]
+    fn global_count_on_the_endgame() {
+        // as many mines as undug cells
+        let board = from_grid(&["ooo", "o**", "o**"]);
+        let mut solver = Solver::new(&board);
+        assert!(solver.global());
+        assert!(solver.known.iter().all(|known| *known == Some(true)));
+
+        // no mines left after the one next to 0,0
+        let board = from_grid(&["oo..", "o*..", "....", "...."]);
+        let mut solver = Solver::new(&board);
+        assert!(solver.single_cell());
+        assert!(solver.global());
+        assert_eq!(known(&solver, (1, 1)), Some(true));
+        for cell in solver.unknown.iter().filter(|cell| **cell != (1, 1)) {
+            assert_eq!(known(&solver, *cell), Some(false));
+        }
+    }
+
+    #[test]
+    fn wrong_flags_are_proved_safe() {
+        let board = from_grid(&["oof", "*.."]);
+        assert_eq!(board.cell_view((0, 2)), CellView::Marked);
+        let deductions = solve(&board);
+        assert!(deductions.safe().contains(&(0, 2)));
+        assert!(!deductions.mines().contains(&(0, 2)));
+    }
+
+    #[test]
+    fn deductions_on_seeded_boards_are_right() {
+        let params = Parameters::new(9, 9, 10).unwrap();
+        let mut deduced = 0;
+        for seed in 0..20 {
+            let mut board = Board::new(&params);
+            board.build_with_rng((4, 4), &mut StdRng::seed_from_u64(seed));
+            let deductions = solve(&board);
+            deduced += deductions.safe().len() + deductions.mines().len();
+            for cell in deductions.safe() {
+                let mut board = Board::new(&params);
+                board.build_with_rng((4, 4), &mut StdRng::seed_from_u64(seed));
+                assert_ne!(board.dig(*cell), GameResult::Lost, "seed {}", seed);
+            }
+            for cell in deductions.mines() {
+                let mut board = Board::new(&params);
+                board.build_with_rng((4, 4), &mut StdRng::seed_from_u64(seed));
+                assert_eq!(board.dig(*cell), GameResult::Lost, "seed {}", seed);
+            }
+        }
+        assert!(deduced > 0);
+    }
+}
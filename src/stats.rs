@@ -0,0 +1,790 @@
+//! Lifetime statistics of the finished games.
+//!
+//! Every finished game is appended as a line to a file under the user
+//!  data directory (ex: `~/.local/share/mineswipper/stats`), or on the
+//!  path of the `MINESWIPPER_STATS` environment variable if set:
+//!
+//! ```text
+//! mineswipper stats 1
+//! 1700000000 9 9 10 won 35120 28 1234 f2bbad31
+//! 1700000100 9 9 10 lost 8200 21 5678 hexagonal 0c830cf9
+//! 1700000200 9 9 10 won 20410 15 9012 wrap c70b541d
+//! ```
+//!
+//! Each line has the unix time when the game ended, the rows, columns
+//!  and mines, the result, the time in milliseconds, the 3BV, the seed,
+//!  the variant of the board (the [topology](Topology) if it isn't square,
+//!  the name of the [neighbourhood](neighbourhood::Neighbourhood) if it isn't the one of
+//!  the topology, or `custom`, the name of the [mask](mask::Mask) if it has one,
+//!  or `mask`, the layers if there are more than one, ex: `3-layers`,
+//!  and `wrap` if its edges wrap) and a checksum of the rest of the line.
+//!
+//! Writes are done while holding a lock file (the stats file path with
+//!  `.lock` appended), so games ending at the same time don't mix
+//!  their lines. A lock left by a game that didn't end well is moved
+//!  away before being removed, so only one game takes it over.
+//!  Lines that can't be read (ex: the game was killed
+//!  while writing) are dropped when the file is loaded, after copying
+//!  the original file to the stats file path with `.corrupt` appended.
+//!
+
+use std::error::Error;
+use std::fmt::Formatter;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{env, fmt};
+use std::{process, thread};
+
+use itertools::Itertools;
+
+use crate::board::save::write_atomically;
+use crate::board::topology::Topology;
+use crate::board::{mask, neighbourhood};
+use crate::board::{Board, GameResult};
+use crate::input::arguments::Preset;
+
+/// Environment variable that overrides the stats file path
+pub const STATS_ENV: &str = "MINESWIPPER_STATS";
+
+/// First line of every stats file
+const MAGIC: &str = "mineswipper stats";
+
+/// Current version of the stats format
+const VERSION: u32 = 1;
+
+/// Time waiting for another game to release the lock
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Locks older than this were left by a game that didn't end well
+const STALE_LOCK: Duration = Duration::from_secs(30);
+
+/// Stats file path, see the [module](self) documentation.
+///
+/// `None` if the user data directory is not known.
+pub fn default_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os(STATS_ENV) {
+        return Some(PathBuf::from(path));
+    }
+    return dirs::data_dir().map(|dir| dir.join("mineswipper").join("stats"));
+}
+
+/// Auxiliary function to append an extension to a path
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    return PathBuf::from(path);
+}
+
+/// FNV-1a hash, used to detect corrupted lines
+fn checksum(text: &str) -> u32 {
+    let mut hash: u32 = 0x811c9dc5;
+    for byte in text.bytes() {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x01000193);
+    }
+    return hash;
+}
+
+/// A finished game.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Record {
+    /// Unix time when the game ended, in seconds
+    timestamp: u64,
+    /// Rows of the board
+    rows: u16,
+    /// Columns of the board
+    columns: u16,
+    /// Mines of the board
+    mines: u32,
+    /// Shape of the cells of the board
+    topology: Topology,
+    /// Name of the neighbourhood of the board, if it isn't the one
+    ///  of the topology, `custom` if it isn't a built-in one
+    neighbourhood: Option<String>,
+    /// Name of the mask of the board, `mask` if it isn't a built-in one
+    mask: Option<String>,
+    /// Layers of the board
+    layers: u16,
+    /// If the edges of the board wrap
+    wrap: bool,
+    /// If the game was won
+    won: bool,
+    /// Time played
+    time: Duration,
+    /// 3BV of the board
+    bbbv: u32,
+    /// Seed of the board, if known
+    seed: Option<u64>,
+}
+
+impl Record {
+    /// Record of a finished game.
+    ///
+    /// `None` if the game didn't end or is a [practice](Board::is_practice) one.
+    pub fn from_board(board: &Board) -> Option<Record> {
+        let won = match board.result() {
+            GameResult::Won => true,
+            GameResult::Lost => false,
+            GameResult::Continue => return None,
+        };
+        if board.is_practice() {
+            return None;
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+        return Some(Record {
+            timestamp,
+            rows: board.rows(),
+            columns: board.columns(),
+            mines: board.mines_count(),
+            topology: board.topology(),
+            neighbourhood: board
+                .neighbourhood()
+                .map(|neighbourhood| neighbourhood.name().unwrap_or("custom").to_string()),
+            mask: board
+                .mask()
+                .map(|mask| mask.name().unwrap_or("mask").to_string()),
+            layers: board.layers(),
+            wrap: board.wraps(),
+            won,
+            time: board.elapsed(),
+            bbbv: board.benchmark().bbbv(),
+            seed: board.seed(),
+        });
+    }
+
+    /// If the game was won
+    pub fn won(&self) -> bool {
+        return self.won;
+    }
+
+    /// Time getter
+    pub fn time(&self) -> Duration {
+        return self.time;
+    }
+
+    /// 3BV getter
+    pub fn bbbv(&self) -> u32 {
+        return self.bbbv;
+    }
+
+    /// Seed getter
+    pub fn seed(&self) -> Option<u64> {
+        return self.seed;
+    }
+
+    /// Name of the preset of the board, or its
+    ///  dimensions if it isn't a preset, ex: `10x20/30`.
+    ///
+    /// Boards that aren't the classic one also have their
+    ///  [variant](Record::variant), ex: `beginner hexagonal wrap`.
+    pub fn category(&self) -> String {
+        let category = match Preset::matching(self.rows, self.columns, self.mines) {
+            Some(preset) => preset.to_string(),
+            None => format!("{}x{}/{}", self.rows, self.columns, self.mines),
+        };
+        return std::iter::once(category).chain(self.variant()).join(" ");
+    }
+
+    /// How the board differs from the classic one, ex: `["hexagonal", "wrap"]`.
+    ///
+    /// Empty for flat square boards that don't wrap.
+    pub fn variant(&self) -> Vec<String> {
+        let mut variant = Vec::new();
+        if self.topology != Topology::Square {
+            variant.push(self.topology.to_string());
+        }
+        if let Some(name) = &self.neighbourhood {
+            variant.push(name.clone());
+        }
+        if let Some(name) = &self.mask {
+            variant.push(name.clone());
+        }
+        if self.layers > 1 {
+            variant.push(format!("{}-layers", self.layers));
+        }
+        if self.wrap {
+            variant.push("wrap".to_string());
+        }
+        return variant;
+    }
+
+    /// Auxiliary method to write the record as a line of the stats file
+    fn to_line(&self) -> String {
+        let seed = match self.seed {
+            Some(seed) => seed.to_string(),
+            None => "none".to_string(),
+        };
+        let content = format!(
+            "{} {} {} {} {} {} {} {}",
+            self.timestamp,
+            self.rows,
+            self.columns,
+            self.mines,
+            if self.won { "won" } else { "lost" },
+            self.time.as_millis(),
+            self.bbbv,
+            seed
+        );
+        let content = std::iter::once(content).chain(self.variant()).join(" ");
+        return format!("{} {:08x}", content, checksum(&content));
+    }
+
+    /// Auxiliary method to read a line of the stats file
+    ///
+    /// `None` if the line is corrupted.
+    fn from_line(line: &str) -> Option<Record> {
+        let (content, hash) = line.rsplit_once(' ')?;
+        if u32::from_str_radix(hash, 16).ok()? != checksum(content) {
+            return None;
+        }
+
+        let fields = content.split(' ').collect::<Vec<&str>>();
+        if fields.len() < 8 {
+            return None;
+        }
+        let mut topology = None;
+        let mut neighbourhood = None;
+        let mut mask = None;
+        let mut layers = None;
+        let mut wrap = false;
+        for word in fields[8..].iter() {
+            match *word {
+                "wrap" if !wrap => wrap = true,
+                word if layers.is_none() && word.ends_with("-layers") => {
+                    match word.trim_end_matches("-layers").parse() {
+                        Ok(n) if n > 1 => layers = Some(n),
+                        _ => return None,
+                    }
+                }
+                name if neighbourhood.is_none()
+                    && (name == "custom" || neighbourhood::PRESETS.contains(&name)) =>
+                {
+                    neighbourhood = Some(name.to_string())
+                }
+                name if mask.is_none() && (name == "mask" || mask::PRESETS.contains(&name)) => {
+                    mask = Some(name.to_string())
+                }
+                word if topology.is_none() => topology = Some(word.parse().ok()?),
+                _ => return None,
+            }
+        }
+        return Some(Record {
+            timestamp: fields[0].parse().ok()?,
+            rows: fields[1].parse().ok()?,
+            columns: fields[2].parse().ok()?,
+            mines: fields[3].parse().ok()?,
+            topology: topology.unwrap_or(Topology::Square),
+            neighbourhood,
+            mask,
+            layers: layers.unwrap_or(1),
+            wrap,
+            won: match fields[4] {
+                "won" => true,
+                "lost" => false,
+                _ => return None,
+            },
+            time: Duration::from_millis(fields[5].parse().ok()?),
+            bbbv: fields[6].parse().ok()?,
+            seed: match fields[7] {
+                "none" => None,
+                seed => Some(seed.parse().ok()?),
+            },
+        });
+    }
+}
+
+/// Returned when the stats file can't be used
+#[derive(Debug)]
+pub enum StatsError {
+    /// The file can't be read or written
+    Io(io::Error),
+    /// Another game is holding the lock for too long
+    Locked(PathBuf),
+    /// The file was written by a newer version of the game
+    UnsupportedVersion(String),
+}
+
+impl Error for StatsError {}
+
+impl fmt::Display for StatsError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            StatsError::Io(e) => write!(f, "Can't use the statistics file: {}", e),
+            StatsError::Locked(path) => write!(
+                f,
+                "The statistics file is locked, remove {} if no game is running",
+                path.display()
+            ),
+            StatsError::UnsupportedVersion(v) => {
+                write!(f, "Unsupported statistics version {}", v)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for StatsError {
+    fn from(e: io::Error) -> StatsError {
+        return StatsError::Io(e);
+    }
+}
+
+/// Exclusive access to the stats file, released when dropped.
+struct Lock {
+    /// Lock file
+    path: PathBuf,
+}
+
+impl Lock {
+    /// Waits until the lock file can be created, for at most `timeout`.
+    fn acquire(stats: &Path, timeout: Duration) -> Result<Lock, StatsError> {
+        let path = with_suffix(stats, ".lock");
+        let mut waited = Duration::ZERO;
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Ok(Lock { path }),
+                Err(e) if e.kind() != io::ErrorKind::AlreadyExists => return Err(e.into()),
+                Err(_) => {}
+            }
+
+            let modified = fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            let stale = modified
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age > STALE_LOCK);
+            if stale {
+                Lock::take_over(&path, modified);
+                continue;
+            }
+
+            if waited >= timeout {
+                return Err(StatsError::Locked(path));
+            }
+            thread::sleep(Duration::from_millis(50));
+            waited += Duration::from_millis(50);
+        }
+    }
+
+    /// Auxiliary method to remove a stale lock, last modified at `modified`.
+    ///
+    /// The lock is renamed first, so when several games find it stale
+    ///  only one of them moves it. If the moved lock isn't the stale
+    ///  one, another game took it over and locked again in between,
+    ///  so it's given back.
+    fn take_over(path: &Path, modified: Option<SystemTime>) {
+        let taken = with_suffix(path, &format!(".{}.stale", process::id()));
+        if fs::rename(path, &taken).is_err() {
+            // another game moved it first
+            return;
+        }
+        let moved = fs::metadata(&taken)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        if moved != modified {
+            // fails if yet another game locked meanwhile, nothing to do then
+            let _ = fs::hard_link(&taken, path);
+        }
+        let _ = fs::remove_file(&taken);
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// The stats file.
+pub struct Store {
+    /// Path of the stats file
+    path: PathBuf,
+}
+
+impl Store {
+    /// Constructor
+    ///
+    /// The file is only created when the first game is recorded.
+    pub fn new<P: AsRef<Path>>(path: P) -> Store {
+        return Store {
+            path: path.as_ref().to_path_buf(),
+        };
+    }
+
+    /// Path getter
+    pub fn path(&self) -> &Path {
+        return &self.path;
+    }
+
+    /// Appends a game to the file.
+    pub fn record(&self, record: &Record) -> Result<(), StatsError> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let _lock = Lock::acquire(&self.path, LOCK_TIMEOUT)?;
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let mut text = String::new();
+        if file.metadata()?.len() == 0 {
+            text.push_str(&format!("{} {}\n", MAGIC, VERSION));
+        }
+        text.push_str(&record.to_line());
+        text.push('\n');
+        // a single write, so a crash leaves at most one broken line
+        file.write_all(text.as_bytes())?;
+        file.sync_data()?;
+        return Ok(());
+    }
+
+    /// Reads all the games of the file.
+    ///
+    /// Corrupted lines are dropped from the file, after the original
+    ///  file is copied to the path with `.corrupt` appended.
+    pub fn load(&self) -> Result<Stats, StatsError> {
+        let _lock = match self.path.exists() {
+            true => Lock::acquire(&self.path, LOCK_TIMEOUT)?,
+            false => return Ok(Stats::default()),
+        };
+
+        let mut records = Vec::new();
+        let mut corrupted = 0;
+        for (i, line) in BufReader::new(File::open(&self.path)?).lines().enumerate() {
+            // invalid UTF-8 is also corruption
+            let line = match line {
+                Ok(line) => line,
+                Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                    corrupted += 1;
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            if i == 0 {
+                if let Some(version) = line.strip_prefix(MAGIC) {
+                    if version.trim() != VERSION.to_string() {
+                        return Err(StatsError::UnsupportedVersion(version.trim().to_string()));
+                    }
+                    continue;
+                }
+                // missing header, the line is checked as a record
+                corrupted += 1;
+            }
+
+            match Record::from_line(&line) {
+                Some(record) => records.push(record),
+                None if line.trim().is_empty() => {}
+                None => corrupted += 1,
+            }
+        }
+
+        if corrupted > 0 {
+            fs::copy(&self.path, with_suffix(&self.path, ".corrupt"))?;
+            write_atomically(&self.path, |writer| {
+                writeln!(writer, "{} {}", MAGIC, VERSION)?;
+                for record in records.iter() {
+                    writeln!(writer, "{}", record.to_line())?;
+                }
+                return Ok(());
+            })?;
+        }
+
+        return Ok(Stats { records, corrupted });
+    }
+}
+
+/// Statistics of a group of games.
+#[derive(Debug, Default)]
+struct Summary {
+    /// Games played
+    games: u32,
+    /// Games won
+    wins: u32,
+    /// Best time of the won games
+    best: Option<Duration>,
+    /// Sum of the times of the won games
+    total_time: Duration,
+}
+
+impl Summary {
+    /// Auxiliary method to add a game
+    fn add(&mut self, record: &Record) {
+        self.games += 1;
+        if record.won {
+            self.wins += 1;
+            self.total_time += record.time;
+            self.best = Some(self.best.map_or(record.time, |best| best.min(record.time)));
+        }
+    }
+
+    /// Average time of the won games
+    fn average(&self) -> Option<Duration> {
+        if self.wins == 0 {
+            return None;
+        }
+        return Some(self.total_time / self.wins);
+    }
+}
+
+/// All the games recorded, see [Store::load].
+#[derive(Debug, Default)]
+pub struct Stats {
+    /// Games, from the oldest
+    records: Vec<Record>,
+    /// Lines dropped because they were corrupted
+    corrupted: usize,
+}
+
+impl Stats {
+    /// Games getter, from the oldest
+    pub fn records(&self) -> &[Record] {
+        return &self.records;
+    }
+
+    /// Number of corrupted lines dropped when loading
+    pub fn corrupted(&self) -> usize {
+        return self.corrupted;
+    }
+
+    /// Games won
+    pub fn wins(&self) -> usize {
+        return self.records.iter().filter(|record| record.won).count();
+    }
+
+    /// Percentage of games won, `None` if there are no games
+    pub fn win_rate(&self) -> Option<f64> {
+        if self.records.is_empty() {
+            return None;
+        }
+        return Some(self.wins() as f64 * 100.0 / self.records.len() as f64);
+    }
+
+    /// Number of the last games with the same result,
+    ///  and if they were won.
+    pub fn current_streak(&self) -> (usize, bool) {
+        let won = match self.records.last() {
+            Some(record) => record.won,
+            None => return (0, false),
+        };
+        let length = self
+            .records
+            .iter()
+            .rev()
+            .take_while(|record| record.won == won)
+            .count();
+        return (length, won);
+    }
+
+    /// Longest sequence of won games
+    pub fn longest_winning_streak(&self) -> usize {
+        let mut longest = 0;
+        let mut current = 0;
+        for record in self.records.iter() {
+            current = if record.won { current + 1 } else { 0 };
+            longest = longest.max(current);
+        }
+        return longest;
+    }
+
+    /// Best time of the won games of each [category](Record::category)
+    pub fn best_times(&self) -> Vec<(String, Duration)> {
+        return self
+            .categories()
+            .into_iter()
+            .filter_map(|(category, summary)| summary.best.map(|best| (category, best)))
+            .collect();
+    }
+
+    /// Average 3BV per second of the won games
+    pub fn average_bbbv_per_second(&self) -> Option<f64> {
+        let rates = self
+            .records
+            .iter()
+            .filter(|record| record.won && !record.time.is_zero())
+            .map(|record| record.bbbv as f64 / record.time.as_secs_f64())
+            .collect::<Vec<f64>>();
+        if rates.is_empty() {
+            return None;
+        }
+        return Some(rates.iter().sum::<f64>() / rates.len() as f64);
+    }
+
+    /// Auxiliary method to group the games by category,
+    ///  keeping the order of the first game of each one.
+    fn categories(&self) -> Vec<(String, Summary)> {
+        let mut categories: Vec<(String, Summary)> = Vec::new();
+        for record in self.records.iter() {
+            let category = record.category();
+            match categories.iter_mut().find(|(name, _)| *name == category) {
+                Some((_, summary)) => summary.add(record),
+                None => {
+                    let mut summary = Summary::default();
+                    summary.add(record);
+                    categories.push((category, summary));
+                }
+            }
+        }
+        return categories;
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let win_rate = match self.win_rate() {
+            Some(win_rate) => win_rate,
+            None => return write!(f, "No games played yet"),
+        };
+        writeln!(
+            f,
+            "games: {}, won: {} ({:.1}%)",
+            self.records.len(),
+            self.wins(),
+            win_rate
+        )?;
+
+        let (length, won) = self.current_streak();
+        writeln!(
+            f,
+            "current streak: {} {}",
+            length,
+            match (won, length) {
+                (true, 1) => "win",
+                (true, _) => "wins",
+                (false, 1) => "loss",
+                (false, _) => "losses",
+            }
+        )?;
+        writeln!(
+            f,
+            "longest winning streak: {}",
+            self.longest_winning_streak()
+        )?;
+        if let Some(rate) = self.average_bbbv_per_second() {
+            writeln!(f, "average 3BV/s of won games: {:.2}", rate)?;
+        }
+
+        for (category, summary) in self.categories() {
+            write!(
+                f,
+                "\n{}: {} games, {} won",
+                category, summary.games, summary.wins
+            )?;
+            if let (Some(best), Some(average)) = (summary.best, summary.average()) {
+                write!(
+                    f,
+                    ", best time {:.1}s, average time {:.1}s",
+                    best.as_secs_f64(),
+                    average.as_secs_f64()
+                )?;
+            }
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Auxiliary function to create an empty directory for a test.
+    fn directory(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("mineswipper-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        return dir;
+    }
+
+    /// Auxiliary function to create a record of a game.
+    fn record(won: bool, seed: Option<u64>) -> Record {
+        return Record {
+            timestamp: 1700000000,
+            rows: 9,
+            columns: 9,
+            mines: 10,
+            topology: Topology::Square,
+            neighbourhood: None,
+            mask: None,
+            layers: 1,
+            wrap: false,
+            won,
+            time: Duration::from_millis(35120),
+            bbbv: 28,
+            seed,
+        };
+    }
+
+    #[test]
+    fn records_are_read_as_written() {
+        let classic = record(true, None);
+        let variant = Record {
+            topology: Topology::Hexagonal,
+            neighbourhood: Some("custom".to_string()),
+            mask: Some("heart".to_string()),
+            layers: 3,
+            wrap: true,
+            ..record(false, Some(1234))
+        };
+        for record in [classic, variant] {
+            assert_eq!(Record::from_line(&record.to_line()), Some(record));
+        }
+    }
+
+    #[test]
+    fn corrupted_lines_are_dropped() {
+        let path = directory("corrupted").join("stats");
+        let good = record(true, Some(1)).to_line();
+        // a lost game changed to a win without updating its checksum
+        let tampered = record(false, Some(2)).to_line().replace("lost", "won");
+        let mut original = format!("{} {}\n{}\n", MAGIC, VERSION, good).into_bytes();
+        // then truncated, garbage and invalid UTF-8 lines
+        original.extend(format!("{}\n", tampered).bytes());
+        original.extend(format!("{}\n", &good[..good.len() / 2]).bytes());
+        original.extend(b"not a game\n\xff\xfe\n");
+        fs::write(&path, &original).unwrap();
+
+        let store = Store::new(&path);
+        let stats = store.load().unwrap();
+        assert_eq!(stats.records(), &[record(true, Some(1))]);
+        assert_eq!(stats.corrupted(), 4);
+        assert_eq!(fs::read(with_suffix(&path, ".corrupt")).unwrap(), original);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format!("{} {}\n{}\n", MAGIC, VERSION, good)
+        );
+        assert_eq!(store.load().unwrap().corrupted(), 0);
+    }
+
+    #[test]
+    fn the_lock_is_exclusive() {
+        let path = directory("lock").join("stats");
+        let lock = Lock::acquire(&path, Duration::ZERO).unwrap();
+        assert!(matches!(
+            Lock::acquire(&path, Duration::ZERO),
+            Err(StatsError::Locked(_))
+        ));
+        drop(lock);
+        assert!(Lock::acquire(&path, Duration::ZERO).is_ok());
+    }
+
+    #[test]
+    fn stale_locks_are_taken_over() {
+        let dir = directory("stale");
+        let path = dir.join("stats");
+        let lock = File::create(with_suffix(&path, ".lock")).unwrap();
+        lock.set_modified(SystemTime::now() - 2 * STALE_LOCK)
+            .unwrap();
+        drop(lock);
+
+        let lock = Lock::acquire(&path, Duration::ZERO).unwrap();
+        // only the new lock is left
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        drop(lock);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+    }
+}
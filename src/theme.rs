@@ -0,0 +1,500 @@
+//! Colours used to print the board.
+//!
+//! A [Theme] is a [Palette], the colour of each kind of symbol,
+//!  and a [Depth], the colours the terminal supports.
+//! The palettes can be built-in presets (see [Palette::preset]),
+//!  some of them safe for colour-blind players, or loaded from a file
+//!  with a `key = value` per line, ex:
+//!
+//! ```text
+//! # start from a preset, the classic one if not given
+//! base = okabe-ito
+//! # numbers from 1 to 8
+//! 1 = #0072b2
+//! 7 = none
+//! # flag, mine, deadly-mine, correct-flag and wrong-flag
+//! flag = #d55e00
+//! ```
+//!
+//! Colours are `#rrggbb` or `none`. On terminals without truecolor
+//!  each colour is replaced by the closest of the 16 basic ones.
+//!
+//! The meaning of the symbols never depends only on their colour,
+//!  so the game can also be played without colours.
+//!
+//! The theme is shared by everything that prints the board, see [current].
+//!
+
+use std::error::Error;
+use std::ffi::OsString;
+use std::fmt::{Display, Formatter};
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use std::sync::RwLock;
+use std::{env, fmt, fs};
+
+/// Environment variable that disables the colours when set,
+///  see <https://no-color.org>
+pub const NO_COLOR_ENV: &str = "NO_COLOR";
+
+/// A colour given by its red, green and blue components
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+/// The 16 basic colours of the terminals, as shown by xterm.
+///
+/// The index is the colour number: 0 to 7 are the normal colours
+///  and 8 to 15 their bright versions.
+const BASIC_COLOURS: [Rgb; 16] = [
+    Rgb(0, 0, 0),
+    Rgb(205, 0, 0),
+    Rgb(0, 205, 0),
+    Rgb(205, 205, 0),
+    Rgb(0, 0, 238),
+    Rgb(205, 0, 205),
+    Rgb(0, 205, 205),
+    Rgb(229, 229, 229),
+    Rgb(127, 127, 127),
+    Rgb(255, 0, 0),
+    Rgb(0, 255, 0),
+    Rgb(255, 255, 0),
+    Rgb(92, 92, 255),
+    Rgb(255, 0, 255),
+    Rgb(0, 255, 255),
+    Rgb(255, 255, 255),
+];
+
+impl Rgb {
+    /// Number of the closest [basic colour](BASIC_COLOURS).
+    fn closest_basic(&self) -> usize {
+        let distance = |other: &Rgb| {
+            let difference = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+            difference(self.0, other.0) + difference(self.1, other.1) + difference(self.2, other.2)
+        };
+        return (0..BASIC_COLOURS.len())
+            .min_by_key(|&i| distance(&BASIC_COLOURS[i]))
+            .unwrap();
+    }
+
+    /// Parses a `#rrggbb` colour.
+    fn parse(value: &str) -> Option<Rgb> {
+        let hex = value.strip_prefix('#')?;
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let component = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap();
+        return Some(Rgb(component(0), component(2), component(4)));
+    }
+}
+
+/// Kinds of symbols printed with their own colour
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Role {
+    /// Dug cell with this number of adjacent mines, from 1 to 8
+    Number(u8),
+    /// Cell marked by the player
+    Flag,
+    /// Mine shown when the game ends
+    Mine,
+    /// The mine dug by the player
+    DeadlyMine,
+    /// Mark over a mine, shown when the game ends
+    CorrectFlag,
+    /// Mark over a cell without mine, shown when the game ends
+    WrongFlag,
+}
+
+/// Colour of each [Role], `None` leaves the symbol with the default colour.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Palette {
+    /// Numbers from 1 to 8
+    numbers: [Option<Rgb>; 8],
+    /// See [Role::Flag]
+    flag: Option<Rgb>,
+    /// See [Role::Mine]
+    mine: Option<Rgb>,
+    /// See [Role::DeadlyMine]
+    deadly_mine: Option<Rgb>,
+    /// See [Role::CorrectFlag]
+    correct_flag: Option<Rgb>,
+    /// See [Role::WrongFlag]
+    wrong_flag: Option<Rgb>,
+}
+
+/// Names of the built-in palettes, see [Palette::preset]
+pub const PRESETS: [&str; 4] = ["classic", "okabe-ito", "tol-bright", "plain"];
+
+impl Palette {
+    /// Numbers based on the ones present in http://minesweeperonline.com/,
+    ///  with 7 and 8 in greys that can be seen on dark terminals.
+    pub const CLASSIC: Palette = Palette {
+        numbers: [
+            Some(Rgb(0, 0, 255)),
+            Some(Rgb(0, 123, 0)),
+            Some(Rgb(255, 0, 0)),
+            Some(Rgb(0, 0, 123)),
+            Some(Rgb(123, 0, 0)),
+            Some(Rgb(0, 123, 123)),
+            Some(Rgb(80, 80, 80)),
+            Some(Rgb(160, 160, 160)),
+        ],
+        flag: Some(Rgb(205, 0, 0)),
+        mine: Some(Rgb(205, 205, 0)),
+        deadly_mine: Some(Rgb(205, 0, 0)),
+        correct_flag: Some(Rgb(0, 205, 0)),
+        wrong_flag: Some(Rgb(205, 0, 0)),
+    };
+
+    /// Okabe and Ito palette, distinguishable with the common
+    ///  kinds of colour blindness.
+    pub const OKABE_ITO: Palette = Palette {
+        numbers: [
+            Some(Rgb(0, 114, 178)),
+            Some(Rgb(0, 158, 115)),
+            Some(Rgb(213, 94, 0)),
+            Some(Rgb(204, 121, 167)),
+            Some(Rgb(230, 159, 0)),
+            Some(Rgb(86, 180, 233)),
+            Some(Rgb(240, 228, 66)),
+            Some(Rgb(153, 153, 153)),
+        ],
+        flag: Some(Rgb(213, 94, 0)),
+        mine: Some(Rgb(230, 159, 0)),
+        deadly_mine: Some(Rgb(213, 94, 0)),
+        correct_flag: Some(Rgb(0, 114, 178)),
+        wrong_flag: Some(Rgb(213, 94, 0)),
+    };
+
+    /// Paul Tol's bright palette, also safe for colour-blind players.
+    pub const TOL_BRIGHT: Palette = Palette {
+        numbers: [
+            Some(Rgb(68, 119, 170)),
+            Some(Rgb(34, 136, 51)),
+            Some(Rgb(238, 102, 119)),
+            Some(Rgb(170, 51, 119)),
+            Some(Rgb(204, 187, 68)),
+            Some(Rgb(102, 204, 238)),
+            Some(Rgb(187, 187, 187)),
+            Some(Rgb(255, 255, 255)),
+        ],
+        flag: Some(Rgb(238, 102, 119)),
+        mine: Some(Rgb(204, 187, 68)),
+        deadly_mine: Some(Rgb(238, 102, 119)),
+        correct_flag: Some(Rgb(68, 119, 170)),
+        wrong_flag: Some(Rgb(238, 102, 119)),
+    };
+
+    /// No colours at all
+    pub const PLAIN: Palette = Palette {
+        numbers: [None; 8],
+        flag: None,
+        mine: None,
+        deadly_mine: None,
+        correct_flag: None,
+        wrong_flag: None,
+    };
+
+    /// Built-in palette with the given name, see [PRESETS].
+    pub fn preset(name: &str) -> Option<Palette> {
+        return match name {
+            "classic" => Some(Palette::CLASSIC),
+            "okabe-ito" => Some(Palette::OKABE_ITO),
+            "tol-bright" => Some(Palette::TOL_BRIGHT),
+            "plain" => Some(Palette::PLAIN),
+            _ => None,
+        };
+    }
+
+    /// Colour of a role
+    pub fn colour(&self, role: Role) -> Option<Rgb> {
+        return match role {
+            Role::Number(n @ 1..=8) => self.numbers[n as usize - 1],
+            Role::Number(_) => None,
+            Role::Flag => self.flag,
+            Role::Mine => self.mine,
+            Role::DeadlyMine => self.deadly_mine,
+            Role::CorrectFlag => self.correct_flag,
+            Role::WrongFlag => self.wrong_flag,
+        };
+    }
+
+    /// Auxiliary method to change the colour of the role with the given key.
+    ///
+    /// Returns `false` if the key is unknown.
+    fn set(&mut self, key: &str, colour: Option<Rgb>) -> bool {
+        let slot = match key {
+            "flag" => &mut self.flag,
+            "mine" => &mut self.mine,
+            "deadly-mine" => &mut self.deadly_mine,
+            "correct-flag" => &mut self.correct_flag,
+            "wrong-flag" => &mut self.wrong_flag,
+            _ => match key.parse::<usize>() {
+                Ok(n @ 1..=8) => &mut self.numbers[n - 1],
+                _ => return false,
+            },
+        };
+        *slot = colour;
+        return true;
+    }
+
+    /// Parses a palette file, see the [module](self) documentation.
+    pub fn parse(content: &str) -> Result<Palette, ThemeError> {
+        let mut base = None;
+        let mut colours = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let number = index + 1;
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => return Err(ThemeError::InvalidLine(number)),
+            };
+
+            if key == "base" {
+                match Palette::preset(value) {
+                    Some(_) if base.is_some() => return Err(ThemeError::InvalidLine(number)),
+                    Some(palette) => base = Some(palette),
+                    None => return Err(ThemeError::UnknownPreset(value.to_string())),
+                }
+                continue;
+            }
+            let colour = match value {
+                "none" => None,
+                _ => match Rgb::parse(value) {
+                    Some(colour) => Some(colour),
+                    None => {
+                        return Err(ThemeError::InvalidColour {
+                            line: number,
+                            value: value.to_string(),
+                        })
+                    }
+                },
+            };
+            colours.push((number, key, colour));
+        }
+
+        let mut palette = base.unwrap_or(Palette::CLASSIC);
+        for (line, key, colour) in colours {
+            if !palette.set(key, colour) {
+                return Err(ThemeError::UnknownKey {
+                    line,
+                    key: key.to_string(),
+                });
+            }
+        }
+        return Ok(palette);
+    }
+
+    /// Loads a palette file, see the [module](self) documentation.
+    pub fn load(path: &Path) -> Result<Palette, ThemeError> {
+        return Palette::parse(&fs::read_to_string(path)?);
+    }
+
+    /// The built-in palette with the given name, or else
+    ///  the palette file on that path if there is one.
+    pub fn find(name: &str) -> Result<Palette, ThemeError> {
+        return match Palette::preset(name) {
+            Some(palette) => Ok(palette),
+            None if Path::new(name).exists() => Palette::load(Path::new(name)),
+            None => Err(ThemeError::UnknownPreset(name.to_string())),
+        };
+    }
+}
+
+/// Returned when a palette file can't be used
+#[derive(Debug)]
+pub enum ThemeError {
+    /// The file can't be read
+    Io(io::Error),
+    /// The line isn't a `key = value`, or repeats the base
+    InvalidLine(usize),
+    /// The key of the line isn't a role
+    UnknownKey {
+        /// number of the line
+        line: usize,
+        /// key found
+        key: String,
+    },
+    /// The value of the line isn't a `#rrggbb` colour nor `none`
+    InvalidColour {
+        /// number of the line
+        line: usize,
+        /// value found
+        value: String,
+    },
+    /// The base, or the theme if there is no file with its
+    ///  name, isn't a built-in palette
+    UnknownPreset(String),
+}
+
+impl Error for ThemeError {}
+
+impl Display for ThemeError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ThemeError::Io(e) => write!(f, "Can't read the theme: {}", e),
+            ThemeError::InvalidLine(line) => write!(f, "Invalid theme line {}", line),
+            ThemeError::UnknownKey { line, key } => {
+                write!(f, "Unknown theme key {:?} on line {}", key, line)
+            }
+            ThemeError::InvalidColour { line, value } => write!(
+                f,
+                "Invalid colour {:?} on line {}, colours are #rrggbb or none",
+                value, line
+            ),
+            ThemeError::UnknownPreset(name) => write!(
+                f,
+                "Unknown theme {:?}, the built-in ones are {}",
+                name,
+                PRESETS.join(", ")
+            ),
+        }
+    }
+}
+
+impl From<io::Error> for ThemeError {
+    fn from(e: io::Error) -> ThemeError {
+        return ThemeError::Io(e);
+    }
+}
+
+/// Colours supported by the terminal
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Depth {
+    /// Any RGB colour
+    TrueColor,
+    /// Only the 16 basic colours
+    Basic,
+    /// No colours
+    Plain,
+}
+
+impl Depth {
+    /// Guesses the colours supported by the standard output.
+    ///
+    /// There are no colours if [NO_COLOR_ENV] is set or the output
+    ///  isn't a terminal, and truecolor is only used if the terminal
+    ///  says so on `COLORTERM`.
+    pub fn detect() -> Depth {
+        return Depth::from_environment(|name| env::var_os(name), io::stdout().is_terminal());
+    }
+
+    /// Same as [detect](Depth::detect) with the given environment
+    ///  variables and if the output is a terminal.
+    pub fn from_environment<F: Fn(&str) -> Option<OsString>>(var: F, terminal: bool) -> Depth {
+        if var(NO_COLOR_ENV).is_some_and(|value| !value.is_empty())
+            || !terminal
+            || var("TERM").is_some_and(|term| term == "dumb")
+        {
+            return Depth::Plain;
+        }
+        return match var("COLORTERM").as_ref().and_then(|value| value.to_str()) {
+            Some("truecolor") | Some("24bit") => Depth::TrueColor,
+            _ => Depth::Basic,
+        };
+    }
+}
+
+/// A palette and the colours that can be used
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Theme {
+    /// Colour of each role
+    palette: Palette,
+    /// Colours supported by the terminal
+    depth: Depth,
+}
+
+/// Theme used by [current] until another is [set](set_current)
+static CURRENT: RwLock<Theme> = RwLock::new(Theme::new(Palette::CLASSIC, Depth::TrueColor));
+
+impl Theme {
+    /// Constructor
+    pub const fn new(palette: Palette, depth: Depth) -> Theme {
+        return Theme { palette, depth };
+    }
+
+    /// Palette getter
+    pub fn palette(&self) -> &Palette {
+        return &self.palette;
+    }
+
+    /// Depth getter
+    pub fn depth(&self) -> Depth {
+        return self.depth;
+    }
+
+    /// Text with the colour of the role, as ANSI escape codes.
+    pub fn paint<T: Display>(&self, role: Role, text: T) -> String {
+        let colour = match (self.depth, self.palette.colour(role)) {
+            (Depth::Plain, _) | (_, None) => return text.to_string(),
+            (_, Some(colour)) => colour,
+        };
+        let code = match self.depth {
+            Depth::TrueColor => format!("38;2;{};{};{}", colour.0, colour.1, colour.2),
+            _ => match colour.closest_basic() {
+                basic @ 0..=7 => (30 + basic).to_string(),
+                basic => (90 + basic - 8).to_string(),
+            },
+        };
+        return format!("\x1b[{}m{}\x1b[0m", code, text);
+    }
+}
+
+/// Theme used to print the board.
+///
+/// The classic palette in truecolor until another is [set](set_current).
+pub fn current() -> Theme {
+    return *CURRENT.read().unwrap_or_else(|e| e.into_inner());
+}
+
+/// Changes the theme used to print the board.
+pub fn set_current(theme: Theme) {
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = theme;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Auxiliary function to detect the depth with only the given variables.
+    fn depth(vars: &[(&str, &str)], terminal: bool) -> Depth {
+        let var = |name: &str| {
+            return vars
+                .iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| OsString::from(value));
+        };
+        return Depth::from_environment(var, terminal);
+    }
+
+    #[test]
+    fn unknown_palettes_are_refused() {
+        assert_eq!(Palette::find("okabe-ito").unwrap(), Palette::OKABE_ITO);
+        let error = Palette::find("no-such-palette").unwrap_err();
+        assert!(matches!(&error, ThemeError::UnknownPreset(name) if name == "no-such-palette"));
+        assert!(error.to_string().contains("classic, okabe-ito"));
+    }
+
+    #[test]
+    fn no_color_disables_the_colours() {
+        let truecolor = ("COLORTERM", "truecolor");
+        assert_eq!(depth(&[truecolor], true), Depth::TrueColor);
+        assert_eq!(depth(&[truecolor, (NO_COLOR_ENV, "1")], true), Depth::Plain);
+        // an empty value doesn't count
+        assert_eq!(
+            depth(&[truecolor, (NO_COLOR_ENV, "")], true),
+            Depth::TrueColor
+        );
+    }
+
+    #[test]
+    fn depth_follows_the_terminal() {
+        assert_eq!(depth(&[], true), Depth::Basic);
+        assert_eq!(depth(&[("COLORTERM", "24bit")], true), Depth::TrueColor);
+        assert_eq!(depth(&[("COLORTERM", "truecolor")], false), Depth::Plain);
+        assert_eq!(depth(&[("TERM", "dumb")], true), Depth::Plain);
+        assert_eq!(depth(&[("TERM", "xterm-256color")], true), Depth::Basic);
+    }
+}
@@ -0,0 +1,433 @@
+//! Full-screen frontend.
+//!
+//! Draws the board on the alternate screen of the terminal and
+//!  redraws it in place after each key. A cursor is moved over the
+//!  board with the arrows (or `hjkl`) and the plays are single keys,
+//!  see [HELP].
+//!
+//! The mouse can also be used, see [mouse] for its buttons.
+//!
+//! On [hexagonal](Topology::Hexagonal) boards the odd rows are
+//!  drawn half a cell (one column) to the right.
+//! Boards whose edges wrap are drawn between two lines of `~`,
+//!  and the cursor goes around their edges.
+//! Boards with layers show the layer of the cursor, `<` and `>`
+//!  (or page up and page down) move the cursor to the other layers.
+//!
+//! The plays go through the same [Session] as the line mode.
+//!
+
+use std::io::{self, Stdout, Write};
+use std::time::Duration;
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyModifiers,
+    MouseButton, MouseEvent, MouseEventKind,
+};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use itertools::Itertools;
+
+use mineswipper::analysis::mine_probabilities;
+use mineswipper::board::BLOCKED;
+use mineswipper::game::Session;
+use mineswipper::input::mouse::{self, Button, Buttons, Click};
+use mineswipper::input::plays::Command;
+use mineswipper::theme::{self, Role, Theme};
+use mineswipper::{CellView, GameResult, Play, PlayMode, Topology};
+
+/// Keys of the interface, shown under the board
+const HELP: &str = "arrows/hjkl: move  d/space: dig  f: flag  c: chord  \
+                    u: undo  r: redo  p: probability  q: quit";
+
+/// Keys of the interface on boards with layers, shown after [HELP]
+const LAYER_HELP: &str = "  </>: layer";
+
+/// How often the timer is redrawn while waiting for a key
+const REFRESH: Duration = Duration::from_millis(200);
+
+/// Screen row of the first row of the board, the status line is above it
+const BOARD_TOP: u16 = 2;
+
+/// Lines of the screen that aren't part of the board:
+///  status, blank line, blank line and help
+const MARGIN: u16 = 4;
+
+/// Takes over the terminal and gives it back as it was when dropped,
+///  even if the game ends with an error.
+struct Screen {
+    stdout: Stdout,
+}
+
+impl Screen {
+    /// Switches to the alternate screen in raw mode.
+    fn enter() -> io::Result<Screen> {
+        terminal::enable_raw_mode()?;
+        let mut screen = Screen {
+            stdout: io::stdout(),
+        };
+        execute!(
+            screen.stdout,
+            EnterAlternateScreen,
+            Hide,
+            EnableMouseCapture
+        )?;
+        return Ok(screen);
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        // nothing else can be done if the terminal can't be restored
+        let _ = execute!(self.stdout, DisableMouseCapture, Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Symbol used to draw a cell, with the colours of the theme,
+///  aligned to the right on the given number of characters.
+fn symbol(theme: &Theme, view: CellView, width: usize) -> String {
+    let length = match view {
+        CellView::Number(n) => n.to_string().len(),
+        _ => 1,
+    };
+    let symbol = match view {
+        CellView::Hidden => "?".to_string(),
+        CellView::Marked => theme.paint(Role::Flag, "X"),
+        CellView::Empty => " ".to_string(),
+        CellView::Mine => theme.paint(Role::DeadlyMine, "*"),
+        CellView::Number(n) => theme.paint(Role::Number(n), n),
+        CellView::Blocked => BLOCKED.to_string(),
+    };
+    return format!("{:1$}{2}", "", width.saturating_sub(length), symbol);
+}
+
+/// Auxiliary function to convert the buttons of the terminal library.
+fn button_of(button: MouseButton) -> Button {
+    return match button {
+        MouseButton::Left => Button::Left,
+        MouseButton::Right => Button::Right,
+        MouseButton::Middle => Button::Middle,
+    };
+}
+
+/// State of the interface
+struct Interface<'a> {
+    /// Game being played
+    session: &'a mut Session,
+    /// Cell under the cursor
+    cursor: (u16, u16),
+    /// First row and column of the layer of the cursor drawn, boards
+    ///  bigger than the screen scroll to keep the cursor visible
+    view: (u16, u16),
+    /// Rows and columns of the board drawn on the last redraw
+    shown: (u16, u16),
+    /// Mouse buttons held
+    buttons: Buttons,
+    /// Shown on the status line until the next key
+    message: String,
+}
+
+impl<'a> Interface<'a> {
+    /// Constructor
+    ///
+    /// The cursor starts on the middle of the first layer of the board.
+    fn new(session: &'a mut Session) -> Interface<'a> {
+        let board = session.board();
+        let cursor = (board.rows() / 2, board.columns() / 2);
+        return Interface {
+            session,
+            cursor,
+            view: (0, 0),
+            shown: (0, 0),
+            buttons: Buttons::new(),
+            message: String::new(),
+        };
+    }
+
+    /// Auxiliary method to move the view so the cursor is visible.
+    fn scroll(&mut self, visible_rows: u16, visible_columns: u16) {
+        let (row, column, _) = self.session.board().position(self.cursor);
+        let scroll = |view: u16, cursor: u16, visible: u16| {
+            if cursor < view {
+                cursor
+            } else if cursor >= view + visible {
+                cursor + 1 - visible
+            } else {
+                view
+            }
+        };
+        self.view = (
+            scroll(self.view.0, row, visible_rows),
+            scroll(self.view.1, column, visible_columns),
+        );
+    }
+
+    /// Screen columns taken by each cell, a space and the biggest
+    ///  number a cell can have.
+    fn cell_width(&self) -> u16 {
+        return self.session.board().cell_width() as u16 + 1;
+    }
+
+    /// Screen columns the shifted rows of hexagonal boards move to the right.
+    fn shift(&self) -> u16 {
+        return match self.session.board().topology() {
+            Topology::Hexagonal => self.cell_width() / 2,
+            Topology::Square => 0,
+        };
+    }
+
+    /// Draws the status line, the visible part of the board and the help.
+    fn draw(&mut self, out: &mut Stdout) -> io::Result<()> {
+        let (width, height) = terminal::size()?;
+        let visible_rows = height.saturating_sub(MARGIN).max(1);
+        let (cell_width, shift) = (self.cell_width(), self.shift());
+        let visible_columns = (width.saturating_sub(shift) / cell_width).max(1);
+        self.scroll(visible_rows, visible_columns);
+
+        let board = self.session.board();
+        let theme = theme::current();
+        let (row, column, layer) = board.position(self.cursor);
+        // first row of the layer of the cursor
+        let first = layer * board.rows();
+        let rows = visible_rows.min(board.rows() - self.view.0);
+        let columns = visible_columns.min(board.columns() - self.view.1);
+        self.shown = (rows, columns);
+        // the blank lines around the board mark the edges that wrap
+        let edge = match board.wraps() {
+            true => "~".repeat((columns * cell_width + shift + 1) as usize),
+            false => String::new(),
+        };
+        let (cell, help) = match board.layers() {
+            1 => (format!("{} {}", row, column), HELP.to_string()),
+            layers => (
+                format!("{} {} {} ({} layers)", row, column, layer, layers),
+                HELP.to_string() + LAYER_HELP,
+            ),
+        };
+        queue!(
+            out,
+            MoveTo(0, 0),
+            Print(format!(
+                "mines left: {}  time: {}s  cell: {}  {}",
+                board.mines_left(),
+                board.elapsed().as_secs(),
+                cell,
+                self.message
+            )),
+            Clear(ClearType::UntilNewLine),
+            MoveTo(0, 1),
+            Print(&edge),
+            Clear(ClearType::UntilNewLine)
+        )?;
+
+        for row in 0..rows {
+            queue!(out, MoveTo(0, BOARD_TOP + row))?;
+            if board.topology().is_shifted(self.view.0 + row) {
+                queue!(out, Print(" ".repeat(shift as usize)))?;
+            }
+            for column in 0..columns {
+                let cell = (first + self.view.0 + row, self.view.1 + column);
+                let symbol = symbol(&theme, board.cell_view(cell), cell_width as usize - 1);
+                queue!(out, Print(' '))?;
+                if cell == self.cursor {
+                    queue!(out, SetAttribute(Attribute::Reverse))?;
+                }
+                queue!(out, Print(symbol), SetAttribute(Attribute::Reset))?;
+            }
+            queue!(out, Clear(ClearType::UntilNewLine))?;
+        }
+
+        queue!(
+            out,
+            MoveTo(0, BOARD_TOP + rows),
+            Clear(ClearType::CurrentLine),
+            Print(&edge),
+            MoveTo(0, BOARD_TOP + rows + 1),
+            Print(help),
+            Clear(ClearType::FromCursorDown)
+        )?;
+        return out.flush();
+    }
+
+    /// Auxiliary method to move the cursor, staying inside the board.
+    ///
+    /// On boards that wrap, the cursor goes to the opposite edge.
+    ///  Layers never wrap.
+    fn move_cursor(&mut self, layers: i32, rows: i32, columns: i32) {
+        let board = self.session.board();
+        let wrap = board.wraps();
+        let clamp = |value: u16, delta: i32, size: u16| match wrap {
+            true => (value as i32 + delta).rem_euclid(size as i32) as u16,
+            false => (value as i32 + delta).clamp(0, size as i32 - 1) as u16,
+        };
+        let (row, column, layer) = board.position(self.cursor);
+        let layer = (layer as i32 + layers).clamp(0, board.layers() as i32 - 1) as u16;
+        self.cursor = (
+            layer * board.rows() + clamp(row, rows, board.rows()),
+            clamp(column, columns, board.columns()),
+        );
+    }
+
+    /// Auxiliary method to build a play over the cell under the cursor.
+    fn play(&self, mode: PlayMode) -> Command {
+        let rows = self.session.board().rows();
+        return Command::Play(Play::layered(mode, self.cursor, rows));
+    }
+
+    /// Cell drawn at a position of the screen, if any.
+    fn cell_at(&self, column: u16, row: u16) -> Option<(u16, u16)> {
+        let board = self.session.board();
+        let row = row.checked_sub(BOARD_TOP)?;
+        let column = match board.topology().is_shifted(self.view.0 + row) {
+            true => column.checked_sub(self.shift())? / self.cell_width(),
+            false => column / self.cell_width(),
+        };
+        if row >= self.shown.0 || column >= self.shown.1 {
+            return None;
+        }
+        let (_, _, layer) = board.position(self.cursor);
+        return Some((
+            layer * board.rows() + self.view.0 + row,
+            self.view.1 + column,
+        ));
+    }
+
+    /// Auxiliary method to show the mine probability of the cell under the cursor.
+    fn show_probability(&mut self) {
+        if !self.session.is_built() {
+            self.message = "Nothing dug yet".to_string();
+            return;
+        }
+        let probabilities = mine_probabilities(self.session.board());
+        self.message = match probabilities.get(self.cursor) {
+            Some(probability) if probabilities.is_approximated(self.cursor) => {
+                format!("mine probability: about {:.0}%", probability * 100.0)
+            }
+            Some(probability) => format!("mine probability: {:.0}%", probability * 100.0),
+            None => "The cell is not hidden".to_string(),
+        };
+    }
+
+    /// Applies a key.
+    ///
+    /// Returns `false` if the player quit.
+    fn handle(&mut self, key: KeyEvent) -> bool {
+        self.message.clear();
+        let command = match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.move_cursor(0, 0, -1);
+                return true;
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.move_cursor(0, 1, 0);
+                return true;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.move_cursor(0, -1, 0);
+                return true;
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.move_cursor(0, 0, 1);
+                return true;
+            }
+            KeyCode::PageUp | KeyCode::Char('<') => {
+                self.move_cursor(-1, 0, 0);
+                return true;
+            }
+            KeyCode::PageDown | KeyCode::Char('>') => {
+                self.move_cursor(1, 0, 0);
+                return true;
+            }
+            KeyCode::Char('p') => {
+                self.show_probability();
+                return true;
+            }
+            KeyCode::Char('d') | KeyCode::Char(' ') | KeyCode::Enter => self.play(PlayMode::Dig),
+            KeyCode::Char('f') | KeyCode::Char('m') => {
+                Command::Play(mouse::flag(self.session.board(), self.cursor))
+            }
+            KeyCode::Char('c') => self.play(PlayMode::Chord),
+            KeyCode::Char('u') => Command::Undo,
+            KeyCode::Char('r') => Command::Redo,
+            _ => return true,
+        };
+        self.apply(command);
+        return true;
+    }
+
+    /// Applies a mouse event, clicks over a cell also move the cursor there.
+    fn click(&mut self, mouse: MouseEvent) {
+        let click = match mouse.kind {
+            MouseEventKind::Down(button) => Click::Press(button_of(button)),
+            MouseEventKind::Up(button) => Click::Release(button_of(button)),
+            _ => return,
+        };
+        self.message.clear();
+
+        let cell = self.cell_at(mouse.column, mouse.row);
+        if let Some(cell) = cell {
+            self.cursor = cell;
+        }
+        if let Some(play) = self.buttons.click(self.session.board(), click, cell) {
+            self.apply(Command::Play(play));
+        }
+    }
+
+    /// Auxiliary method to apply a command to the session
+    ///  and show what the player should be told.
+    fn apply(&mut self, command: Command) {
+        let notices = self.session.apply(command);
+        self.message = notices.iter().join(". ");
+
+        let board = self.session.board();
+        let end = match board.result() {
+            GameResult::Continue => return,
+            GameResult::Won => "You won",
+            GameResult::Lost => "You lost",
+        };
+        let hint = match board.practice_mode() {
+            true => "u: undo, q: quit",
+            false => "press any key or click",
+        };
+        self.message = format!("{}, {}", end, hint);
+    }
+
+    /// Reads keys and clicks until the player quits or the game ends.
+    fn run(&mut self, out: &mut Stdout) -> io::Result<()> {
+        loop {
+            self.draw(out)?;
+            // the timer is redrawn even without events
+            if !event::poll(REFRESH)? {
+                continue;
+            }
+            let event = event::read()?;
+
+            let board = self.session.board();
+            let over = board.result() != GameResult::Continue && !board.practice_mode();
+            match event {
+                Event::Key(_)
+                | Event::Mouse(MouseEvent {
+                    kind: MouseEventKind::Down(_),
+                    ..
+                }) if over => return Ok(()),
+                Event::Key(key) if !self.handle(key) => return Ok(()),
+                Event::Mouse(mouse) => self.click(mouse),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Plays the game of the session on the full-screen interface,
+///  until the player quits or the game ends.
+///
+/// The terminal is restored before returning.
+pub fn play(session: &mut Session) -> io::Result<()> {
+    let mut screen = Screen::enter()?;
+    return Interface::new(session).run(&mut screen.stdout);
+}